@@ -1,110 +1,921 @@
+use std::{collections::HashSet, sync::Arc};
+
 use async_trait::async_trait;
-use habfoo_api_generated::{
-  models::{self, FoodItemInner, Nutrient},
-  AddMealResponse, Api, CreateFoodItemResponse, FetchMealsResponse, GetFoodItemResponse,
-  RemoveMealResponse, SearchFoodItemResponse,
-};
 use log::info;
-use swagger::{ApiError, Has, XSpanIdString};
+use swagger::ApiError;
+
+use crate::{
+  auth,
+  nutrients::{self, FoodItemSource},
+  repository::{FoodItemSearch, MealRecord, Repository, UserRecord},
+  summary,
+  traits::{
+    self, AddMealResultType, CreateFoodItemResultType, CreateUserResultType, Credentials,
+    DeleteFoodItemResultType, GetFoodItemNutrientsResultType, GetFoodItemResultType, HabFoo,
+    RemoveMealResultType, UpdateFoodItemResultType,
+  },
+  types::{
+    AddMealRequest, AddMealResponse, CreateFoodItemResult, CreateUserRequest, CreateUserResponse,
+    DeleteFoodItemResponse, DeleteMealResponse, FetchMealsRequest, FetchMealsResponse,
+    FoodItemInUse, FoodItemInner, FoodItemLink, FoodItemLinkType, FoodItemValidationError,
+    GetAllNutrientsResponse, Meal, MealValidationError, NutritionSummaryRequest,
+    NutritionSummaryResponse, SearchInput, SearchItemResult, UserValidationError,
+    ValidationViolation,
+  },
+  validation,
+};
+
+/// Amount of food item links returned for a single search request.
+const SEARCH_PAGE_SIZE: u64 = 20;
+
+/// User, authenticated by bearer token. Meals are kept and shown per user, food items are shared
+/// by everyone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct User {
+  id: String,
+}
+
+fn into_meal(id: String, meal: MealRecord) -> Meal {
+  Meal {
+    id: Some(id),
+    consume_time: meal.consume_time,
+    food_item_id: meal.food_item_id,
+    amount: meal.amount,
+    tag: meal.tag,
+  }
+}
+
+fn into_link(id: String, item: FoodItemInner) -> FoodItemLink {
+  if let Some(branded) = item.branded {
+    FoodItemLink {
+      title: branded.title,
+      brand: Some(branded.brand),
+      r#type: FoodItemLinkType::Branded,
+      id,
+    }
+  } else if let Some(composite) = item.composite {
+    FoodItemLink {
+      title: composite.title,
+      brand: None,
+      r#type: FoodItemLinkType::Composite,
+      id,
+    }
+  } else {
+    FoodItemLink {
+      title: item.fundamental.map(|f| f.title).unwrap_or_default(),
+      brand: None,
+      r#type: FoodItemLinkType::Fundamental,
+      id,
+    }
+  }
+}
 
 #[derive(Clone)]
 pub struct HabFooApi {
-  database_client: mongodb::Client,
+  repository: Arc<dyn Repository>,
 }
 
 impl HabFooApi {
-  pub async fn new(mongodb_uri: &str) -> Self {
-    let database_client = mongodb::Client::with_uri_str(mongodb_uri)
-      .await
-      .unwrap_or_else(|_| panic!("Cannot create mongodb client with addr {}", mongodb_uri));
-    info!("Good");
-    HabFooApi { database_client }
+  pub fn new<R>(repository: R) -> Self
+  where
+    R: Repository,
+  {
+    HabFooApi {
+      repository: Arc::new(repository),
+    }
+  }
+
+  async fn find_meals(
+    &self,
+    user: &User,
+    start_time: Option<chrono::DateTime<chrono::Utc>>,
+    end_time: Option<chrono::DateTime<chrono::Utc>>,
+  ) -> Result<Vec<Meal>, ApiError> {
+    Ok(
+      self
+        .repository
+        .meals(&user.id, start_time, end_time)
+        .await?
+        .into_iter()
+        .map(|(id, meal)| into_meal(id, meal))
+        .collect(),
+    )
   }
 
-  async fn get_collection<T>(&self, name: &str) -> Option<mongodb::Collection<T>> {
-    self
-      .database_client
-      .default_database()
-      .map(|db| db.collection(name))
+  /// Food item with composite items, which contain it directly or through other composites.
+  async fn with_composites(&self, item_id: &str) -> Result<Vec<String>, ApiError> {
+    let mut found = HashSet::new();
+    let mut ids = vec![item_id.to_owned()];
+    found.insert(item_id.to_owned());
+    let mut index = 0;
+    while let Some(id) = ids.get(index).cloned() {
+      for composite_id in self.repository.composites_containing(&id).await? {
+        if found.insert(composite_id.clone()) {
+          ids.push(composite_id);
+        }
+      }
+      index += 1;
+    }
+    Ok(ids)
+  }
+
+  /// Barcodes of branded items are unique, so the item must not take barcode of another one.
+  async fn barcode_violation(
+    &self,
+    item_id: Option<&str>,
+    item: &FoodItemInner,
+  ) -> Result<Option<ValidationViolation>, ApiError> {
+    let barcode = match &item.branded {
+      Some(branded) => branded.barcode.clone(),
+      None => return Ok(None),
+    };
+    let search = FoodItemSearch {
+      title: None,
+      barcode: Some(barcode),
+    };
+    let found = self.repository.search_food_items(&search, 0, 1).await?;
+    Ok(
+      found
+        .into_iter()
+        .find(|(id, _)| Some(id.as_str()) != item_id)
+        .map(|(id, _)| ValidationViolation {
+          path: "/branded/barcode".to_owned(),
+          message: format!("belongs to food item {id}"),
+        }),
+    )
   }
 
-  async fn get_food_item_collection(&self) -> Option<mongodb::Collection<FoodItemInner>> {
-    self.get_collection("foodItems").await
+  /// Removes food items together with meals of the user with them.
+  async fn delete_food_items(
+    &self,
+    user: &User,
+    deleted_food_item_ids: Vec<String>,
+  ) -> Result<DeleteFoodItemResponse, ApiError> {
+    let mut deleted_meal_count = 0;
+    for id in deleted_food_item_ids.iter() {
+      deleted_meal_count += self
+        .repository
+        .remove_meals_with_food_item(&user.id, id)
+        .await?;
+      self.repository.remove_food_item(id).await?;
+    }
+    Ok(DeleteFoodItemResponse {
+      deleted_food_item_ids,
+      deleted_meal_count: deleted_meal_count as i64,
+    })
   }
 }
 
 #[async_trait]
-impl<C> Api<C> for HabFooApi
-where
-  C: Has<XSpanIdString> + Send + Sync,
-{
+impl FoodItemSource for HabFooApi {
+  async fn food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError> {
+    self.repository.food_item(id).await
+  }
+}
+
+#[async_trait]
+impl HabFoo for HabFooApi {
+  type User = User;
+
+  async fn authenticate(&self, credentials: Credentials) -> Result<Option<User>, traits::Error> {
+    let Credentials::BearerAuth(token) = credentials;
+    let id = self
+      .repository
+      .user_by_token_hash(&auth::token_hash(&token))
+      .await?;
+    Ok(id.map(|id| User { id }))
+  }
+
+  async fn create_user(
+    &self,
+    create_user_request: CreateUserRequest,
+  ) -> Result<CreateUserResultType, traits::Error> {
+    let violations = validation::validate_user(&create_user_request);
+    if !violations.is_empty() {
+      return Ok(CreateUserResultType::Result_422(UserValidationError {
+        violations,
+      }));
+    }
+    let name = create_user_request.name.trim().to_owned();
+    let token = auth::new_token();
+    let id = self
+      .repository
+      .insert_user(UserRecord {
+        name,
+        token_hash: auth::token_hash(&token),
+      })
+      .await?;
+    Ok(CreateUserResultType::Result_200(CreateUserResponse {
+      id,
+      token,
+    }))
+  }
+
   async fn add_meal(
     &self,
-    add_meal_request: models::AddMealRequest,
-    context: &C,
-  ) -> Result<AddMealResponse, ApiError> {
-    Err(ApiError("Not implemented".into()))
+    user: User,
+    add_meal_request: AddMealRequest,
+  ) -> Result<AddMealResultType, traits::Error> {
+    let meal = MealRecord {
+      user_id: user.id,
+      consume_time: add_meal_request.consume_time,
+      food_item_id: add_meal_request.food_item_id,
+      amount: add_meal_request.amount,
+      tag: add_meal_request.tag,
+    };
+    let violations = validation::validate_meal(self, &meal).await?;
+    if !violations.is_empty() {
+      return Ok(AddMealResultType::Result_422(MealValidationError {
+        violations,
+      }));
+    }
+    let created_meal_id = self.repository.insert_meal(meal).await?;
+    Ok(AddMealResultType::Result_200(AddMealResponse {
+      created_meal_id: Some(created_meal_id),
+    }))
   }
 
   async fn fetch_meals(
     &self,
-    fetch_meals_query: Option<models::FetchMealsRequest>,
-    context: &C,
-  ) -> Result<FetchMealsResponse, ApiError> {
-    Err(ApiError("Not implemented".into()))
+    user: User,
+    fetch_meals_query: Option<FetchMealsRequest>,
+  ) -> Result<FetchMealsResponse, traits::Error> {
+    let (start_time, end_time) = fetch_meals_query
+      .map(|query| (query.start_time, query.end_time))
+      .unwrap_or_default();
+    let items = self.find_meals(&user, start_time, end_time).await?;
+    Ok(FetchMealsResponse { items: Some(items) })
+  }
+
+  async fn get_nutrition_summary(
+    &self,
+    user: User,
+    nutrition_summary_query: Option<NutritionSummaryRequest>,
+  ) -> Result<NutritionSummaryResponse, traits::Error> {
+    let (start_time, end_time) = nutrition_summary_query
+      .map(|query| (query.start_time, query.end_time))
+      .unwrap_or_default();
+    let meals = self.find_meals(&user, start_time, end_time).await?;
+    Ok(summary::summarize(self, &meals).await?)
   }
 
   async fn remove_meal(
     &self,
+    user: User,
     meal_id: String,
-    context: &C,
-  ) -> Result<RemoveMealResponse, ApiError> {
-    Err(ApiError("Not implemented".into()))
+  ) -> Result<RemoveMealResultType, traits::Error> {
+    let meal = match self.repository.remove_meal(&user.id, &meal_id).await? {
+      Some(meal) => into_meal(meal_id, meal),
+      None => return Ok(RemoveMealResultType::Result_404),
+    };
+    Ok(RemoveMealResultType::Result_200(DeleteMealResponse {
+      id: meal.id,
+      consume_time: meal.consume_time,
+      food_item_id: meal.food_item_id,
+      amount: meal.amount,
+      tag: meal.tag,
+    }))
   }
 
   async fn create_food_item(
     &self,
-    food_item_inner: models::FoodItemInner,
-    _context: &C,
-  ) -> Result<CreateFoodItemResponse, ApiError> {
-    if let Some(collection) = self.get_food_item_collection().await {
-      collection
-        .insert_one(food_item_inner, None)
-        .await
-        .map(|result| {
-          CreateFoodItemResponse::FoodItemCreatedWithNewId(models::CreateFoodItemResult {
-            id: result.inserted_id.as_str().unwrap().into(),
-          })
-        })
-        .map_err(|mongo_err| ApiError(format!("{mongo_err}")))
-    } else {
-      Err(ApiError("Cannot get collection".into()))
+    food_item_inner: FoodItemInner,
+  ) -> Result<CreateFoodItemResultType, traits::Error> {
+    let mut violations = validation::validate_food_item(self, &food_item_inner).await?;
+    violations.extend(self.barcode_violation(None, &food_item_inner).await?);
+    if !violations.is_empty() {
+      return Ok(CreateFoodItemResultType::Result_422(
+        FoodItemValidationError { violations },
+      ));
     }
+    let id = self.repository.insert_food_item(food_item_inner).await?;
+    Ok(CreateFoodItemResultType::Result_200(CreateFoodItemResult {
+      id,
+    }))
   }
 
-  async fn get_food_item(
+  async fn update_food_item(
     &self,
+    _user: User,
     item_id: String,
-    context: &C,
-  ) -> Result<GetFoodItemResponse, ApiError> {
-    info!(
-      "get_food_item({:?}) - X-Span-ID: {:?}",
-      item_id,
-      context.get().0.clone()
+    food_item_inner: FoodItemInner,
+  ) -> Result<UpdateFoodItemResultType, traits::Error> {
+    if self.food_item(&item_id).await?.is_none() {
+      return Ok(UpdateFoodItemResultType::Result_404);
+    }
+    let mut violations =
+      validation::validate_food_item_update(self, &item_id, &food_item_inner).await?;
+    violations.extend(
+      self
+        .barcode_violation(Some(&item_id), &food_item_inner)
+        .await?,
     );
-    Err(ApiError("Not implemented".into()))
+    if !violations.is_empty() {
+      return Ok(UpdateFoodItemResultType::Result_422(
+        FoodItemValidationError { violations },
+      ));
+    }
+    let updated = self
+      .repository
+      .update_food_item(&item_id, food_item_inner.clone())
+      .await?;
+    Ok(if updated {
+      UpdateFoodItemResultType::Result_200(food_item_inner)
+    } else {
+      UpdateFoodItemResultType::Result_404
+    })
+  }
+
+  async fn delete_food_item(
+    &self,
+    user: User,
+    item_id: String,
+    cascade: Option<bool>,
+  ) -> Result<DeleteFoodItemResultType, traits::Error> {
+    if self.food_item(&item_id).await?.is_none() {
+      return Ok(DeleteFoodItemResultType::Result_404);
+    }
+    let cascade = cascade.unwrap_or(false);
+    let composite_ids = self.repository.composites_containing(&item_id).await?;
+    let meal_count = self
+      .repository
+      .count_meals_with_food_item(&user.id, &item_id)
+      .await?;
+    let deleted_ids = if cascade {
+      self.with_composites(&item_id).await?
+    } else {
+      vec![item_id.clone()]
+    };
+    // Meals of other users are never deleted, so items they refer to are kept.
+    let mut used_by_others = false;
+    for id in deleted_ids.iter() {
+      used_by_others |= self
+        .repository
+        .food_item_in_meals_of_others(&user.id, id)
+        .await?;
+    }
+    let in_use = !composite_ids.is_empty() || meal_count > 0;
+    if used_by_others || in_use && !cascade {
+      let message = if used_by_others {
+        format!("Food item {item_id} or composite items containing it are in meals of other users")
+      } else {
+        format!(
+          "Food item {item_id} is in {} composite food items and {meal_count} meals, \
+           delete it with cascade to delete them as well",
+          composite_ids.len()
+        )
+      };
+      return Ok(DeleteFoodItemResultType::Result_409(FoodItemInUse {
+        message,
+        composite_ids,
+        meal_count: meal_count as i64,
+        used_by_others,
+      }));
+    }
+    let deleted = self.delete_food_items(&user, deleted_ids).await?;
+    Ok(DeleteFoodItemResultType::Result_200(deleted))
+  }
+
+  async fn get_food_item(&self, item_id: String) -> Result<GetFoodItemResultType, traits::Error> {
+    info!("get_food_item({:?})", item_id);
+    Ok(match self.food_item(&item_id).await? {
+      Some(item) => GetFoodItemResultType::Result_200(item),
+      None => GetFoodItemResultType::Result_404,
+    })
+  }
+
+  async fn get_food_item_nutrients(
+    &self,
+    item_id: String,
+  ) -> Result<GetFoodItemNutrientsResultType, traits::Error> {
+    let items = nutrients::load_composition(self, &item_id).await?;
+    if !items.contains_key(&item_id) {
+      return Ok(GetFoodItemNutrientsResultType::Result_404);
+    }
+    Ok(match nutrients::rollup_loaded(&items, &item_id) {
+      Ok(profile) => GetFoodItemNutrientsResultType::Result_200(GetAllNutrientsResponse {
+        nutrients: Some(profile.into_nutrients()),
+      }),
+      Err(err) => GetFoodItemNutrientsResultType::Result_422(FoodItemValidationError {
+        violations: vec![validation::composition_violation(&err)],
+      }),
+    })
   }
 
   async fn search_food_item(
     &self,
-    search_input: Option<models::SearchInput>,
-    context: &C,
-  ) -> Result<SearchFoodItemResponse, ApiError> {
-    info!(
-      "search_food_item({:?}) - X-Span-ID: {:?}",
-      search_input,
-      context.get().0.clone()
+    search_input: Option<SearchInput>,
+  ) -> Result<SearchItemResult, traits::Error> {
+    info!("search_food_item({:?})", search_input);
+    let (title, barcode, next_marker) = search_input
+      .map(|input| (input.title, input.barcode, input.next_marker))
+      .unwrap_or_default();
+    let skip = next_marker.unwrap_or(0).max(0) as u64;
+    let search = FoodItemSearch { title, barcode };
+    // One extra item tells whether there is a next page.
+    let mut items = self
+      .repository
+      .search_food_items(&search, skip, SEARCH_PAGE_SIZE + 1)
+      .await?
+      .into_iter()
+      .map(|(id, item)| into_link(id, item))
+      .collect::<Vec<_>>();
+    let next_marker = if items.len() as u64 > SEARCH_PAGE_SIZE {
+      items.truncate(SEARCH_PAGE_SIZE as usize);
+      Some((skip + SEARCH_PAGE_SIZE) as i64)
+    } else {
+      None
+    };
+    Ok(SearchItemResult { next_marker, items })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::{
+    repository::MemoryRepository,
+    types::{
+      AddMealRequestTag, BrandedFoodItem, CalculatedFoodItem, CompositeFoodItem,
+      FundamentalFoodItem, Nutrient, NutrientKind,
+    },
+  };
+
+  fn api() -> HabFooApi {
+    HabFooApi::new(MemoryRepository::default())
+  }
+
+  async fn user(api: &HabFooApi, name: &str) -> User {
+    let token = match api
+      .create_user(CreateUserRequest { name: name.into() })
+      .await
+      .unwrap()
+    {
+      CreateUserResultType::Result_200(created) => created.token,
+      CreateUserResultType::Result_422(_) => panic!("Valid user is not created"),
+    };
+    api
+      .authenticate(Credentials::BearerAuth(token))
+      .await
+      .unwrap()
+      .unwrap()
+  }
+
+  fn fundamental(title: &str) -> FoodItemInner {
+    FoodItemInner {
+      fundamental: Some(FundamentalFoodItem {
+        title: title.into(),
+        description: None,
+        nutrients: vec![Nutrient {
+          percentage: 10.0,
+          kind: NutrientKind::Protein,
+        }],
+      }),
+      composite: None,
+      branded: None,
+    }
+  }
+
+  fn composite(title: &str, ids: &[&str]) -> FoodItemInner {
+    FoodItemInner {
+      fundamental: None,
+      composite: Some(CompositeFoodItem {
+        title: title.into(),
+        description: None,
+        composition: ids
+          .iter()
+          .map(|id| CalculatedFoodItem {
+            amount: 100.0,
+            food_item: FoodItemLink {
+              title: id.to_string(),
+              brand: None,
+              r#type: FoodItemLinkType::Fundamental,
+              id: id.to_string(),
+            },
+          })
+          .collect(),
+      }),
+      branded: None,
+    }
+  }
+
+  async fn create(api: &HabFooApi, item: FoodItemInner) -> String {
+    match api.create_food_item(item).await.unwrap() {
+      CreateFoodItemResultType::Result_200(created) => created.id,
+      CreateFoodItemResultType::Result_422(_) => panic!("Valid food item is not created"),
+    }
+  }
+
+  async fn add(api: &HabFooApi, user: &User, meal: AddMealRequest) -> String {
+    match api.add_meal(user.clone(), meal).await.unwrap() {
+      AddMealResultType::Result_200(added) => added.created_meal_id.unwrap(),
+      _ => panic!("Valid meal is not added"),
+    }
+  }
+
+  fn meal_of(food_item_id: &str, hour: u32) -> AddMealRequest {
+    AddMealRequest {
+      consume_time: Utc.ymd(2022, 6, 1).and_hms(hour, 0, 0),
+      food_item_id: food_item_id.into(),
+      amount: 100.0,
+      tag: Some(AddMealRequestTag::Breakfast),
+    }
+  }
+
+  #[tokio::test]
+  async fn fetches_meals_in_range_sorted_by_consume_time() {
+    let api = api();
+    let user = user(&api, "alice").await;
+    let egg = create(&api, fundamental("Egg")).await;
+    for hour in [12, 8, 20, 10] {
+      add(&api, &user, meal_of(&egg, hour)).await;
+    }
+
+    let meals = api
+      .fetch_meals(
+        user,
+        Some(FetchMealsRequest {
+          start_time: Some(Utc.ymd(2022, 6, 1).and_hms(9, 0, 0)),
+          end_time: Some(Utc.ymd(2022, 6, 1).and_hms(20, 0, 0)),
+        }),
+      )
+      .await
+      .unwrap()
+      .items
+      .unwrap();
+
+    let hours = meals
+      .iter()
+      .map(|meal| meal.consume_time.format("%H").to_string())
+      .collect::<Vec<_>>();
+    assert_eq!(hours, vec!["10", "12"]);
+  }
+
+  #[tokio::test]
+  async fn removes_meal_and_returns_it() {
+    let api = api();
+    let user = user(&api, "alice").await;
+    let egg = create(&api, fundamental("Egg")).await;
+    let id = add(&api, &user, meal_of(&egg, 8)).await;
+
+    match api.remove_meal(user.clone(), id.clone()).await.unwrap() {
+      RemoveMealResultType::Result_200(removed) => {
+        assert_eq!(removed.id, Some(id.clone()));
+        assert_eq!(removed.amount, 100.0);
+      }
+      RemoveMealResultType::Result_404 => panic!("Meal is not removed"),
+    }
+    assert!(api
+      .fetch_meals(user.clone(), None)
+      .await
+      .unwrap()
+      .items
+      .unwrap()
+      .is_empty());
+    assert_eq!(
+      api.remove_meal(user, id).await.unwrap(),
+      RemoveMealResultType::Result_404
+    );
+  }
+
+  #[tokio::test]
+  async fn keeps_meals_of_users_apart() {
+    let api = api();
+    let alice = user(&api, "alice").await;
+    let bob = user(&api, "bob").await;
+    let egg = create(&api, fundamental("Egg")).await;
+    let id = add(&api, &alice, meal_of(&egg, 8)).await;
+
+    let bob_meals = api.fetch_meals(bob.clone(), None).await.unwrap().items;
+    assert!(bob_meals.unwrap().is_empty());
+    assert_eq!(
+      api.remove_meal(bob, id.clone()).await.unwrap(),
+      RemoveMealResultType::Result_404
+    );
+    let alice_meals = api.fetch_meals(alice, None).await.unwrap().items.unwrap();
+    assert_eq!(alice_meals.len(), 1);
+    assert_eq!(alice_meals[0].id, Some(id));
+  }
+
+  #[tokio::test]
+  async fn refuses_meal_with_unknown_food_item() {
+    let api = api();
+    let user = user(&api, "alice").await;
+
+    match api.add_meal(user.clone(), meal_of("404", 8)).await.unwrap() {
+      AddMealResultType::Result_422(error) => {
+        assert_eq!(error.violations[0].path, "/foodItemId");
+      }
+      _ => panic!("Meal with unknown food item is added"),
+    }
+    assert!(api
+      .fetch_meals(user, None)
+      .await
+      .unwrap()
+      .items
+      .unwrap()
+      .is_empty());
+  }
+
+  #[tokio::test]
+  async fn does_not_authenticate_unknown_token() {
+    let api = api();
+    user(&api, "alice").await;
+
+    let user = api
+      .authenticate(Credentials::BearerAuth("unknown".into()))
+      .await
+      .unwrap();
+    assert_eq!(user, None);
+  }
+
+  #[tokio::test]
+  async fn refuses_invalid_food_item_with_all_violations() {
+    let api = api();
+    let mut item = fundamental("");
+    let nutrients = &mut item.fundamental.as_mut().unwrap().nutrients;
+    nutrients.push(Nutrient {
+      percentage: 120.0,
+      kind: NutrientKind::Protein,
+    });
+    nutrients.push(Nutrient {
+      percentage: 250.0,
+      kind: NutrientKind::Energy,
+    });
+
+    let violations = match api.create_food_item(item).await.unwrap() {
+      CreateFoodItemResultType::Result_422(error) => error.violations,
+      CreateFoodItemResultType::Result_200(_) => panic!("Invalid food item is created"),
+    };
+    let paths = violations
+      .iter()
+      .map(|v| v.path.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      paths,
+      vec![
+        "/fundamental/title",
+        "/fundamental/nutrients/1/percentage",
+        "/fundamental/nutrients/1/kind"
+      ]
     );
-    Err(ApiError("Not implemented".into()))
+  }
+
+  #[tokio::test]
+  async fn searches_titles_case_insensitively_by_pages() {
+    let api = api();
+    for n in 0..SEARCH_PAGE_SIZE + 5 {
+      api
+        .create_food_item(fundamental(&format!("Chicken egg {n}")))
+        .await
+        .unwrap();
+    }
+    api.create_food_item(fundamental("Milk")).await.unwrap();
+
+    let search = |next_marker| SearchInput {
+      title: Some("EGG".into()),
+      barcode: None,
+      next_marker,
+    };
+    let first = api.search_food_item(Some(search(None))).await.unwrap();
+    assert_eq!(first.items.len() as u64, SEARCH_PAGE_SIZE);
+    assert_eq!(first.next_marker, Some(SEARCH_PAGE_SIZE as i64));
+
+    let second = api
+      .search_food_item(Some(search(first.next_marker)))
+      .await
+      .unwrap();
+    assert_eq!(second.items.len(), 5);
+    assert_eq!(second.next_marker, None);
+    assert!(second
+      .items
+      .iter()
+      .all(|link| link.title.starts_with("Chicken egg")));
+  }
+
+  #[tokio::test]
+  async fn deletes_item_in_use_only_with_cascade() {
+    let api = api();
+    let user = user(&api, "alice").await;
+    let egg = create(&api, fundamental("Egg")).await;
+    let milk = create(&api, fundamental("Milk")).await;
+    let omelette = create(&api, composite("Omelette", &[&egg, &milk])).await;
+    let breakfast = create(&api, composite("Breakfast", &[&omelette])).await;
+    add(&api, &user, meal_of(&egg, 8)).await;
+    add(&api, &user, meal_of(&breakfast, 9)).await;
+    add(&api, &user, meal_of(&milk, 10)).await;
+
+    match api
+      .delete_food_item(user.clone(), egg.clone(), None)
+      .await
+      .unwrap()
+    {
+      DeleteFoodItemResultType::Result_409(in_use) => {
+        assert_eq!(in_use.composite_ids, vec![omelette.clone()]);
+        assert_eq!(in_use.meal_count, 1);
+      }
+      _ => panic!("Food item in use is deleted without cascade"),
+    }
+    assert!(api.food_item(&egg).await.unwrap().is_some());
+
+    match api
+      .delete_food_item(user.clone(), egg.clone(), Some(true))
+      .await
+      .unwrap()
+    {
+      DeleteFoodItemResultType::Result_200(deleted) => {
+        assert_eq!(
+          deleted.deleted_food_item_ids,
+          vec![egg, omelette, breakfast]
+        );
+        assert_eq!(deleted.deleted_meal_count, 2);
+      }
+      _ => panic!("Food item is not deleted with cascade"),
+    }
+    let meals = api
+      .fetch_meals(user.clone(), None)
+      .await
+      .unwrap()
+      .items
+      .unwrap();
+    assert_eq!(meals.len(), 1);
+    assert_eq!(meals[0].food_item_id, milk);
+    assert!(matches!(
+      api.delete_food_item(user, milk, None).await.unwrap(),
+      DeleteFoodItemResultType::Result_409(_)
+    ));
+  }
+
+  #[tokio::test]
+  async fn keeps_food_items_in_meals_of_other_users() {
+    let api = api();
+    let alice = user(&api, "alice").await;
+    let bob = user(&api, "bob").await;
+    let egg = create(&api, fundamental("Egg")).await;
+    let milk = create(&api, fundamental("Milk")).await;
+    let omelette = create(&api, composite("Omelette", &[&egg])).await;
+    add(&api, &alice, meal_of(&egg, 8)).await;
+    add(&api, &bob, meal_of(&milk, 8)).await;
+    let bob_omelette = add(&api, &bob, meal_of(&omelette, 9)).await;
+
+    match api
+      .delete_food_item(alice.clone(), egg.clone(), Some(true))
+      .await
+      .unwrap()
+    {
+      DeleteFoodItemResultType::Result_409(in_use) => {
+        assert!(in_use.used_by_others);
+        assert_eq!(in_use.meal_count, 1);
+      }
+      _ => panic!("Food item in meals of other user is deleted"),
+    }
+    assert!(api.food_item(&omelette).await.unwrap().is_some());
+
+    api.remove_meal(bob.clone(), bob_omelette).await.unwrap();
+    match api
+      .delete_food_item(alice.clone(), egg.clone(), Some(true))
+      .await
+      .unwrap()
+    {
+      DeleteFoodItemResultType::Result_200(deleted) => {
+        assert_eq!(deleted.deleted_food_item_ids, vec![egg, omelette]);
+        assert_eq!(deleted.deleted_meal_count, 1);
+      }
+      _ => panic!("Food item is not deleted with cascade"),
+    }
+    let bob_meals = api.fetch_meals(bob, None).await.unwrap().items.unwrap();
+    assert_eq!(bob_meals.len(), 1);
+    assert_eq!(bob_meals[0].food_item_id, milk);
+    assert!(api
+      .fetch_meals(alice, None)
+      .await
+      .unwrap()
+      .items
+      .unwrap()
+      .is_empty());
+  }
+
+  #[tokio::test]
+  async fn refuses_update_making_composition_cycle() {
+    let api = api();
+    let user = user(&api, "alice").await;
+    let egg = create(&api, fundamental("Egg")).await;
+    let omelette = create(&api, composite("Omelette", &[&egg])).await;
+    let breakfast = create(&api, composite("Breakfast", &[&omelette])).await;
+
+    let cyclic = composite("Omelette", &[&egg, &breakfast]);
+    match api
+      .update_food_item(user.clone(), omelette.clone(), cyclic)
+      .await
+      .unwrap()
+    {
+      UpdateFoodItemResultType::Result_422(error) => {
+        assert_eq!(error.violations[0].path, "/composite/composition");
+      }
+      _ => panic!("Cyclic composition is saved"),
+    }
+
+    let renamed = composite("Scrambled eggs", &[&egg]);
+    assert!(matches!(
+      api
+        .update_food_item(user.clone(), omelette, renamed)
+        .await
+        .unwrap(),
+      UpdateFoodItemResultType::Result_200(_)
+    ));
+    assert!(matches!(
+      api
+        .update_food_item(user, "404".into(), fundamental("Nothing"))
+        .await
+        .unwrap(),
+      UpdateFoodItemResultType::Result_404
+    ));
+  }
+
+  #[tokio::test]
+  async fn rolls_up_nutrients_and_reports_composition_cycle() {
+    let api = api();
+    let egg = create(&api, fundamental("Egg")).await;
+    let omelette = create(&api, composite("Omelette", &[&egg])).await;
+    let breakfast = create(&api, composite("Breakfast", &[&omelette])).await;
+
+    match api
+      .get_food_item_nutrients(breakfast.clone())
+      .await
+      .unwrap()
+    {
+      GetFoodItemNutrientsResultType::Result_200(response) => {
+        assert_eq!(
+          response.nutrients,
+          fundamental("Egg").fundamental.map(|f| f.nutrients)
+        );
+      }
+      _ => panic!("Nutrients of composite item are not rolled up"),
+    }
+    assert!(matches!(
+      api.get_food_item_nutrients("404".into()).await.unwrap(),
+      GetFoodItemNutrientsResultType::Result_404
+    ));
+
+    // Cycles are refused on update, so this one is written around the validation
+    let cyclic = composite("Omelette", &[&egg, &breakfast]);
+    api
+      .repository
+      .update_food_item(&omelette, cyclic)
+      .await
+      .unwrap();
+    match api.get_food_item_nutrients(breakfast).await.unwrap() {
+      GetFoodItemNutrientsResultType::Result_422(error) => {
+        assert_eq!(error.violations[0].path, "/composite/composition");
+        assert!(error.violations[0].message.starts_with("makes a cycle"));
+      }
+      _ => panic!("Cyclic composition is rolled up"),
+    }
+  }
+
+  #[tokio::test]
+  async fn refuses_user_with_empty_name() {
+    match api()
+      .create_user(CreateUserRequest { name: " ".into() })
+      .await
+      .unwrap()
+    {
+      CreateUserResultType::Result_422(error) => assert_eq!(error.violations[0].path, "/name"),
+      CreateUserResultType::Result_200(_) => panic!("User with empty name is created"),
+    }
+  }
+
+  #[tokio::test]
+  async fn keeps_barcodes_unique() {
+    let api = api();
+    let user = user(&api, "alice").await;
+    let branded = |title: &str| FoodItemInner {
+      fundamental: None,
+      composite: None,
+      branded: Some(BrandedFoodItem {
+        title: title.into(),
+        brand: "Dairy".into(),
+        barcode: "0012345".into(),
+        description: None,
+        nutrients: Vec::new(),
+      }),
+    };
+    let milk = create(&api, branded("Milk")).await;
+
+    match api.create_food_item(branded("Kefir")).await.unwrap() {
+      CreateFoodItemResultType::Result_422(error) => {
+        assert_eq!(error.violations[0].path, "/branded/barcode");
+      }
+      _ => panic!("Second item with the same barcode is created"),
+    }
+    assert!(matches!(
+      api
+        .update_food_item(user, milk, branded("Whole milk"))
+        .await
+        .unwrap(),
+      UpdateFoodItemResultType::Result_200(_)
+    ));
   }
 }
@@ -0,0 +1,19 @@
+//! Bearer tokens of users.
+//!
+//! Token is a random secret, which is returned to the user once on registration. Repositories
+//! keep only its hash, so leaked database doesn't let anyone act as the user.
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Fresh random token, hex encoded.
+pub fn new_token() -> String {
+  let mut bytes = [0u8; 32];
+  rand::thread_rng().fill_bytes(&mut bytes);
+  hex::encode(bytes)
+}
+
+/// Hash of the token, under which user is stored.
+pub fn token_hash(token: &str) -> String {
+  hex::encode(Sha256::digest(token.as_bytes()))
+}
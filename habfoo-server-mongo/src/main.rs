@@ -1,31 +1,53 @@
-// mod api_impl;
-// mod server;
-//
+mod api_impl;
+mod auth;
+mod nutrients;
+mod off_import;
+mod repository;
+mod server;
+mod summary;
+mod validation;
+
+use api_impl::HabFooApi;
 use openapi_types_generator::types;
-use routerify;
+use repository::{MemoryRepository, MongoRepository};
 
-types!("habfoo-api/api/root.yaml");
+types!("../habfoo-api/api/root.yaml");
 
-async fn run() {
-  /*
-  env_logger::init();
+async fn mongo_repository() -> MongoRepository {
   let mongodb_user = std::env::var("MONGODB_USER").expect("MONGODB_USER is a mandatory var");
   let mongodb_db = std::env::var("MONGODB_DB").expect("MONGODB_DB is a mandatory var");
   let mongodb_password =
     std::env::var("MONGODB_PASSWD").expect("MONGODB_PASSWD is a mandatory var");
   let mongodb_host = std::env::var("MONGODB_HOST").expect("MONGODB_HOST is a mandatory var");
-  let addr = "127.0.0.1:8080";
   let mongodb_addr_url =
-    format!("mongodb://{mongodb_user}:{mongodb_password}@{mongodb_host}:27019/habfoo");
+    format!("mongodb://{mongodb_user}:{mongodb_password}@{mongodb_host}:27019/{mongodb_db}");
+  MongoRepository::new(&mongodb_addr_url).await
+}
 
-  let schema = oas3::from_path_dir("habfoo-api/api", "root.yaml").unwrap();
-  let search_food_itam = schema.paths.get("/search-food-item").unwrap();
-  let parameter = &search_food_itam.get.as_ref().unwrap().parameters[0].resolve(&schema).unwrap();
+/// `import <dump>` fills branded food items from Open Food Facts dump, see [`off_import`].
+async fn import(path: &str) {
+  let report = off_import::import_file(&mongo_repository().await, std::path::Path::new(path))
+    .await
+    .unwrap_or_else(|err| panic!("Cannot import {path}: {}", err.0));
+  println!("Imported {path}: {report}");
+}
 
-  println!("{:#?}", parameter.schema.as_ref().unwrap().resolve(&schema));
-  */
+async fn run() {
+  env_logger::init();
+  let args = std::env::args().collect::<Vec<_>>();
+  if let [_, command, path] = args.as_slice() {
+    if command == "import" {
+      return import(path).await;
+    }
+  }
+  let addr = "127.0.0.1:8080";
+  // HABFOO_STORAGE=memory runs server without database, everything is lost on restart
+  let api = match std::env::var("HABFOO_STORAGE").as_deref() {
+    Ok("memory") => HabFooApi::new(MemoryRepository::default()),
+    _ => HabFooApi::new(mongo_repository().await),
+  };
 
-  // server::create(addr, &mongodb_addr_url).await;
+  server::create(addr, api).await;
 }
 
 #[tokio::main]
@@ -0,0 +1,274 @@
+//! Nutrient rollup for food items.
+//!
+//! Fundamental and branded items carry their nutrients directly, composite items are recipes
+//! made of other items. Rollup walks the composition graph and turns a recipe into a flat profile
+//! with the same meaning as for fundamental food: percentage of each nutrient in the item.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use async_trait::async_trait;
+use swagger::ApiError;
+
+use crate::types::{FoodItemInner, Nutrient, NutrientKind};
+
+/// Anything, that is able to find food item by its id.
+#[async_trait]
+pub trait FoodItemSource {
+  async fn food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RollupError {
+  /// Recipes refer to each other. Ids go from the first recipe around the cycle and back to it.
+  Cycle(Vec<String>),
+  /// Composition refers to an item, which does not exist.
+  MissingItem(String),
+  /// Item has neither fundamental, nor branded, nor composite data.
+  EmptyItem(String),
+}
+
+/// Percentage of each nutrient kind in a food item.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NutrientProfile(BTreeMap<NutrientKind, f64>);
+
+impl NutrientProfile {
+  fn from_nutrients(nutrients: &[Nutrient]) -> Self {
+    let mut profile = NutrientProfile::default();
+    for nutrient in nutrients {
+      profile.add(nutrient.kind, nutrient.percentage);
+    }
+    profile
+  }
+
+  fn add(&mut self, kind: NutrientKind, percentage: f64) {
+    *self.0.entry(kind).or_insert(0.0) += percentage;
+  }
+
+  #[cfg(test)]
+  pub fn percentage(&self, kind: NutrientKind) -> f64 {
+    self.0.get(&kind).copied().unwrap_or(0.0)
+  }
+
+  /// Nutrient kinds with amount of each nutrient in `amount` of the item.
+  pub fn amounts(&self, amount: f64) -> impl Iterator<Item = (NutrientKind, f64)> + '_ {
+    self
+      .0
+      .iter()
+      .map(move |(kind, percentage)| (*kind, percentage * amount / 100.0))
+  }
+
+  pub fn into_nutrients(self) -> Vec<Nutrient> {
+    self
+      .0
+      .into_iter()
+      .map(|(kind, percentage)| Nutrient { percentage, kind })
+      .collect()
+  }
+}
+
+/// Fetches the whole composition graph starting from `id`. Every item is fetched once, so cycles
+/// don't make it loop; they are reported later by [`rollup_loaded`].
+pub async fn load_composition<S>(
+  source: &S,
+  id: &str,
+) -> Result<HashMap<String, FoodItemInner>, ApiError>
+where
+  S: FoodItemSource + Sync,
+{
+  let mut items = HashMap::new();
+  let mut requested = HashSet::new();
+  let mut queue = vec![id.to_owned()];
+  requested.insert(id.to_owned());
+
+  while let Some(id) = queue.pop() {
+    if let Some(item) = source.food_item(&id).await? {
+      for calculated in item.composite.iter().flat_map(|c| c.composition.iter()) {
+        let child_id = &calculated.food_item.id;
+        if requested.insert(child_id.clone()) {
+          queue.push(child_id.clone());
+        }
+      }
+      items.insert(id, item);
+    }
+  }
+  Ok(items)
+}
+
+/// Rolls up item `id` using already loaded items.
+pub fn rollup_loaded(
+  items: &HashMap<String, FoodItemInner>,
+  id: &str,
+) -> Result<NutrientProfile, RollupError> {
+  Rollup {
+    items,
+    profiles: HashMap::new(),
+    stack: Vec::new(),
+  }
+  .profile(id)
+}
+
+struct Rollup<'a> {
+  items: &'a HashMap<String, FoodItemInner>,
+  profiles: HashMap<String, NutrientProfile>,
+  stack: Vec<String>,
+}
+
+impl<'a> Rollup<'a> {
+  fn profile(&mut self, id: &str) -> Result<NutrientProfile, RollupError> {
+    if let Some(profile) = self.profiles.get(id) {
+      return Ok(profile.clone());
+    }
+    if let Some(position) = self.stack.iter().position(|visited| visited == id) {
+      let mut cycle = self.stack[position..].to_vec();
+      cycle.push(id.to_owned());
+      return Err(RollupError::Cycle(cycle));
+    }
+
+    let item = self
+      .items
+      .get(id)
+      .ok_or_else(|| RollupError::MissingItem(id.to_owned()))?;
+    let profile = if let Some(fundamental) = &item.fundamental {
+      NutrientProfile::from_nutrients(&fundamental.nutrients)
+    } else if let Some(branded) = &item.branded {
+      NutrientProfile::from_nutrients(&branded.nutrients)
+    } else if let Some(composite) = &item.composite {
+      self.stack.push(id.to_owned());
+      let mut total_amount = 0.0;
+      let mut weighted = NutrientProfile::default();
+      for calculated in composite.composition.iter() {
+        let child = self.profile(&calculated.food_item.id)?;
+        total_amount += calculated.amount;
+        for (kind, percentage) in child.0 {
+          weighted.add(kind, percentage * calculated.amount);
+        }
+      }
+      self.stack.pop();
+
+      if total_amount > 0.0 {
+        weighted
+          .0
+          .values_mut()
+          .for_each(|percentage| *percentage /= total_amount);
+        weighted
+      } else {
+        NutrientProfile::default()
+      }
+    } else {
+      return Err(RollupError::EmptyItem(id.to_owned()));
+    };
+
+    self.profiles.insert(id.to_owned(), profile.clone());
+    Ok(profile)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::types::{
+    CalculatedFoodItem, CompositeFoodItem, FoodItemLink, FoodItemLinkType, FundamentalFoodItem,
+  };
+
+  fn fundamental(protein: f64, fat: f64) -> FoodItemInner {
+    FoodItemInner {
+      fundamental: Some(FundamentalFoodItem {
+        title: "fundamental".into(),
+        description: None,
+        nutrients: vec![
+          Nutrient {
+            percentage: protein,
+            kind: NutrientKind::Protein,
+          },
+          Nutrient {
+            percentage: fat,
+            kind: NutrientKind::Fat,
+          },
+        ],
+      }),
+      composite: None,
+      branded: None,
+    }
+  }
+
+  fn composite(parts: &[(&str, f64)]) -> FoodItemInner {
+    FoodItemInner {
+      fundamental: None,
+      composite: Some(CompositeFoodItem {
+        title: "composite".into(),
+        description: None,
+        composition: parts
+          .iter()
+          .map(|(id, amount)| CalculatedFoodItem {
+            amount: *amount,
+            food_item: FoodItemLink {
+              title: id.to_string(),
+              brand: None,
+              r#type: FoodItemLinkType::Composite,
+              id: id.to_string(),
+            },
+          })
+          .collect(),
+      }),
+      branded: None,
+    }
+  }
+
+  fn items(items: Vec<(&str, FoodItemInner)>) -> HashMap<String, FoodItemInner> {
+    items
+      .into_iter()
+      .map(|(id, item)| (id.to_owned(), item))
+      .collect()
+  }
+
+  #[test]
+  fn rolls_up_nested_composition_weighted_by_amount() {
+    let items = items(vec![
+      ("egg", fundamental(12.0, 10.0)),
+      ("oil", fundamental(0.0, 100.0)),
+      ("omelette", composite(&[("egg", 90.0), ("oil", 10.0)])),
+      (
+        "breakfast",
+        composite(&[("omelette", 100.0), ("egg", 100.0)]),
+      ),
+    ]);
+
+    let omelette = rollup_loaded(&items, "omelette").unwrap();
+    assert!((omelette.percentage(NutrientKind::Protein) - 10.8).abs() < 1e-9);
+    assert!((omelette.percentage(NutrientKind::Fat) - 19.0).abs() < 1e-9);
+
+    let breakfast = rollup_loaded(&items, "breakfast").unwrap();
+    assert!((breakfast.percentage(NutrientKind::Protein) - 11.4).abs() < 1e-9);
+    assert!((breakfast.percentage(NutrientKind::Fat) - 14.5).abs() < 1e-9);
+  }
+
+  #[test]
+  fn reports_cycle_between_recipes() {
+    let items = items(vec![
+      ("egg", fundamental(12.0, 10.0)),
+      ("a", composite(&[("egg", 10.0), ("b", 10.0)])),
+      ("b", composite(&[("c", 10.0)])),
+      ("c", composite(&[("a", 10.0)])),
+    ]);
+
+    assert_eq!(
+      rollup_loaded(&items, "a"),
+      Err(RollupError::Cycle(vec![
+        "a".into(),
+        "b".into(),
+        "c".into(),
+        "a".into()
+      ]))
+    );
+  }
+
+  #[test]
+  fn reports_missing_item() {
+    let items = items(vec![("a", composite(&[("nothing", 10.0)]))]);
+
+    assert_eq!(
+      rollup_loaded(&items, "a"),
+      Err(RollupError::MissingItem("nothing".into()))
+    );
+  }
+}
@@ -0,0 +1,393 @@
+//! Import of branded food items from Open Food Facts dumps.
+//!
+//! Both exports of <https://world.openfoodfacts.org/data> are read: CSV (tab separated as OFF
+//! publishes it, comma separated works as well) and JSONL with one product per line. Files with
+//! `.jsonl` or `.json` extension are read as JSONL, anything else as CSV.
+//!
+//! Every product becomes a [`BrandedFoodItem`] upserted by its barcode, so importing a newer dump
+//! updates items in place instead of duplicating them. Nutrients are taken per 100 g, energy is
+//! kept in kcal.
+
+use std::{
+  collections::HashMap,
+  fmt,
+  fs::File,
+  io::{BufRead, BufReader, Read},
+  path::Path,
+};
+
+use log::{debug, info};
+use swagger::ApiError;
+
+use crate::{
+  repository::Repository,
+  types::{BrandedFoodItem, Nutrient, NutrientKind},
+};
+
+/// Kilojoules in one kilocalorie, energy of old products is given in kJ only.
+const KJ_PER_KCAL: f64 = 4.184;
+
+/// OFF nutriment fields for each of our nutrient kinds, the first one found is used.
+const NUTRIMENTS: &[(NutrientKind, &[&str])] = &[
+  (NutrientKind::Protein, &["proteins_100g"]),
+  (NutrientKind::Carbohydrates, &["carbohydrates_100g"]),
+  (NutrientKind::Fat, &["fat_100g"]),
+  (NutrientKind::Calcium, &["calcium_100g"]),
+  (NutrientKind::Omega3, &["omega-3-fat_100g"]),
+  (NutrientKind::Fiber, &["fiber_100g"]),
+];
+
+/// Fields of OFF product we need, the same for both kinds of dumps.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Product {
+  code: String,
+  name: String,
+  brands: String,
+  /// Numeric nutriments by their OFF names, like `proteins_100g`.
+  nutriments: HashMap<String, f64>,
+}
+
+impl Product {
+  fn from_json(mut value: serde_json::Value) -> Self {
+    let text = |value: &serde_json::Value, field: &str| match value.get(field) {
+      Some(serde_json::Value::String(text)) => text.clone(),
+      Some(serde_json::Value::Number(number)) => number.to_string(),
+      _ => String::new(),
+    };
+    let nutriments = match value.get_mut("nutriments").map(serde_json::Value::take) {
+      Some(serde_json::Value::Object(nutriments)) => nutriments
+        .into_iter()
+        .filter_map(|(name, value)| number(&value).map(|number| (name, number)))
+        .collect(),
+      _ => HashMap::new(),
+    };
+    Product {
+      code: text(&value, "code"),
+      name: text(&value, "product_name"),
+      brands: text(&value, "brands"),
+      nutriments,
+    }
+  }
+
+  fn from_csv(headers: &csv::StringRecord, record: &csv::StringRecord) -> Self {
+    let mut product = Product::default();
+    for (header, field) in headers.iter().zip(record.iter()) {
+      match header {
+        "code" => product.code = field.to_owned(),
+        "product_name" => product.name = field.to_owned(),
+        "brands" => product.brands = field.to_owned(),
+        nutriment if nutriment.ends_with("_100g") => {
+          if let Ok(number) = field.trim().parse() {
+            product.nutriments.insert(nutriment.to_owned(), number);
+          }
+        }
+        _ => {}
+      }
+    }
+    product
+  }
+
+  fn nutriment(&self, names: &[&str]) -> Option<f64> {
+    names
+      .iter()
+      .find_map(|name| self.nutriments.get(*name))
+      .copied()
+      .filter(|value| value.is_finite() && *value >= 0.0)
+  }
+
+  fn energy_kcal(&self) -> Option<f64> {
+    self
+      .nutriment(&["energy-kcal_100g"])
+      .or_else(|| self.nutriment(&["energy-kj_100g", "energy_100g"]).map(|kj| kj / KJ_PER_KCAL))
+  }
+
+  /// Branded food item, if product has everything it needs.
+  fn branded_item(&self) -> Result<BrandedFoodItem, SkipReason> {
+    // Barcode is kept as text, numbers would lose leading zeros of UPC codes.
+    let barcode = self.code.trim();
+    if barcode.is_empty() {
+      return Err(SkipReason::Barcode);
+    }
+    let title = self.name.trim();
+    if title.is_empty() {
+      return Err(SkipReason::Title);
+    }
+    // OFF lists brands comma separated, the first one is the owner of the product.
+    let brand = self
+      .brands
+      .split(',')
+      .map(str::trim)
+      .find(|brand| !brand.is_empty())
+      .ok_or(SkipReason::Brand)?;
+
+    let energy = self.energy_kcal().map(|kcal| Nutrient {
+      percentage: kcal,
+      kind: NutrientKind::Energy,
+    });
+    let nutrients = energy
+      .into_iter()
+      .chain(NUTRIMENTS.iter().filter_map(|(kind, names)| {
+        self
+          .nutriment(names)
+          .filter(|percentage| *percentage <= 100.0)
+          .map(|percentage| Nutrient {
+            percentage,
+            kind: *kind,
+          })
+      }))
+      .collect();
+
+    Ok(BrandedFoodItem {
+      title: title.to_owned(),
+      brand: brand.to_owned(),
+      barcode: barcode.to_owned(),
+      description: None,
+      nutrients,
+    })
+  }
+}
+
+fn number(value: &serde_json::Value) -> Option<f64> {
+  match value {
+    serde_json::Value::Number(number) => number.as_f64(),
+    serde_json::Value::String(text) => text.trim().parse().ok(),
+    _ => None,
+  }
+}
+
+/// Why product is not imported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SkipReason {
+  Barcode,
+  Title,
+  Brand,
+}
+
+impl fmt::Display for SkipReason {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SkipReason::Barcode => write!(f, "no barcode"),
+      SkipReason::Title => write!(f, "no product name"),
+      SkipReason::Brand => write!(f, "no brand"),
+    }
+  }
+}
+
+/// Numbers of imported and skipped products.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ImportReport {
+  pub created: u64,
+  pub updated: u64,
+  pub skipped: u64,
+}
+
+impl fmt::Display for ImportReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} created, {} updated, {} skipped",
+      self.created, self.updated, self.skipped
+    )
+  }
+}
+
+struct Importer<'a, R: ?Sized> {
+  repository: &'a R,
+  report: ImportReport,
+}
+
+impl<'a, R> Importer<'a, R>
+where
+  R: Repository + ?Sized,
+{
+  async fn import(&mut self, product: Product) -> Result<(), ApiError> {
+    match product.branded_item() {
+      Ok(item) => {
+        if self.repository.upsert_branded_food_item(item).await? {
+          self.report.created += 1;
+        } else {
+          self.report.updated += 1;
+        }
+      }
+      Err(reason) => {
+        debug!("Product {:?} is skipped: {reason}", product.code);
+        self.report.skipped += 1;
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Imports products from JSONL dump, one product object per line.
+pub async fn import_jsonl<R, I>(repository: &R, input: I) -> Result<ImportReport, ApiError>
+where
+  R: Repository + ?Sized,
+  I: BufRead,
+{
+  let mut importer = Importer {
+    repository,
+    report: ImportReport::default(),
+  };
+  for (index, line) in input.lines().enumerate() {
+    let line = line.map_err(|err| ApiError(format!("Cannot read line {}: {err}", index + 1)))?;
+    if line.trim().is_empty() {
+      continue;
+    }
+    let value = serde_json::from_str(&line)
+      .map_err(|err| ApiError(format!("Line {} is not a JSON: {err}", index + 1)))?;
+    importer.import(Product::from_json(value)).await?;
+  }
+  Ok(importer.report)
+}
+
+/// Imports products from CSV dump with header line. Delimiter is tab, if header has one, comma
+/// otherwise.
+pub async fn import_csv<R, I>(repository: &R, mut input: I) -> Result<ImportReport, ApiError>
+where
+  R: Repository + ?Sized,
+  I: BufRead,
+{
+  let mut header = String::new();
+  input
+    .read_line(&mut header)
+    .map_err(|err| ApiError(format!("Cannot read CSV header: {err}")))?;
+  let tab_separated = header.contains('\t');
+  // OFF does not quote fields of its tab separated export, quotes are just part of the text.
+  let mut reader = csv::ReaderBuilder::new()
+    .delimiter(if tab_separated { b'\t' } else { b',' })
+    .quoting(!tab_separated)
+    .flexible(true)
+    .from_reader(header.as_bytes().chain(input));
+  let headers = reader
+    .headers()
+    .map_err(|err| ApiError(format!("Cannot read CSV header: {err}")))?
+    .clone();
+
+  let mut importer = Importer {
+    repository,
+    report: ImportReport::default(),
+  };
+  for record in reader.records() {
+    let record = record.map_err(|err| ApiError(format!("Cannot read CSV record: {err}")))?;
+    importer.import(Product::from_csv(&headers, &record)).await?;
+  }
+  Ok(importer.report)
+}
+
+/// Imports OFF dump file, format is chosen by extension.
+pub async fn import_file<R>(repository: &R, path: &Path) -> Result<ImportReport, ApiError>
+where
+  R: Repository + ?Sized,
+{
+  let file = File::open(path)
+    .map_err(|err| ApiError(format!("Cannot open {}: {err}", path.display())))?;
+  let input = BufReader::new(file);
+  let is_jsonl = matches!(
+    path.extension().and_then(|extension| extension.to_str()),
+    Some("jsonl" | "json")
+  );
+  info!("Importing {}", path.display());
+  if is_jsonl {
+    import_jsonl(repository, input).await
+  } else {
+    import_csv(repository, input).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::repository::{FoodItemSearch, MemoryRepository};
+
+  const JSONL: &str = r#"{"code":"4607065001234","product_name":"Kefir 3.2%","brands":"Prostokvashino, Danone","nutriments":{"energy-kcal_100g":59,"proteins_100g":"2.9","fat_100g":3.2,"carbohydrates_100g":4}}
+{"code":"","product_name":"No barcode","brands":"Nobody"}
+{"code":"3017620422003","product_name":"Nutella","brands":"Ferrero","nutriments":{"energy_100g":2252,"fat_100g":30.9,"fiber_100g":0}}
+"#;
+
+  #[tokio::test]
+  async fn imports_jsonl_with_nutrients_per_100_grams() {
+    let repository = MemoryRepository::default();
+    let report = import_jsonl(&repository, JSONL.as_bytes()).await.unwrap();
+    assert_eq!(
+      report,
+      ImportReport {
+        created: 2,
+        updated: 0,
+        skipped: 1
+      }
+    );
+
+    let search = FoodItemSearch {
+      title: None,
+      barcode: Some("4607065001234".into()),
+    };
+    let found = repository.search_food_items(&search, 0, 10).await.unwrap();
+    let kefir = found[0].1.branded.clone().unwrap();
+    assert_eq!(kefir.brand, "Prostokvashino");
+    let nutrients = kefir
+      .nutrients
+      .iter()
+      .map(|nutrient| (nutrient.kind, nutrient.percentage))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      nutrients,
+      vec![
+        (NutrientKind::Energy, 59.0),
+        (NutrientKind::Protein, 2.9),
+        (NutrientKind::Carbohydrates, 4.0),
+        (NutrientKind::Fat, 3.2),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn reimport_updates_items_by_barcode() {
+    let repository = MemoryRepository::default();
+    import_jsonl(&repository, JSONL.as_bytes()).await.unwrap();
+    let csv = "code\tproduct_name\tbrands\tenergy-kcal_100g\tfat_100g\n\
+               3017620422003\tNutella \"new\"\tFerrero\t539\t30.9\n";
+    let report = import_csv(&repository, csv.as_bytes()).await.unwrap();
+    assert_eq!(
+      report,
+      ImportReport {
+        created: 0,
+        updated: 1,
+        skipped: 0
+      }
+    );
+
+    let all = repository
+      .search_food_items(&FoodItemSearch::default(), 0, 10)
+      .await
+      .unwrap();
+    assert_eq!(all.len(), 2);
+    let nutella = all
+      .iter()
+      .find_map(|(_, item)| item.branded.clone().filter(|b| b.brand == "Ferrero"))
+      .unwrap();
+    assert_eq!(nutella.title, "Nutella \"new\"");
+    assert_eq!(nutella.nutrients.len(), 2);
+  }
+
+  #[tokio::test]
+  async fn keeps_leading_zeros_of_barcodes() {
+    let repository = MemoryRepository::default();
+    let csv = "code\tproduct_name\tbrands\n0049000028911\tCola\tCoca-Cola\n";
+    import_csv(&repository, csv.as_bytes()).await.unwrap();
+
+    let search = |barcode: &str| FoodItemSearch {
+      title: None,
+      barcode: Some(barcode.into()),
+    };
+    let found = repository
+      .search_food_items(&search("0049000028911"), 0, 10)
+      .await
+      .unwrap();
+    assert_eq!(found.len(), 1);
+    let stripped = repository
+      .search_food_items(&search("49000028911"), 0, 10)
+      .await
+      .unwrap();
+    assert!(stripped.is_empty());
+  }
+}
@@ -0,0 +1,261 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use swagger::ApiError;
+
+use super::{FoodItemSearch, MealRecord, Repository, UserRecord};
+use crate::types::{BrandedFoodItem, FoodItemInner};
+
+#[derive(Default)]
+struct State {
+  last_id: u64,
+  users: BTreeMap<u64, UserRecord>,
+  food_items: BTreeMap<u64, FoodItemInner>,
+  meals: BTreeMap<u64, MealRecord>,
+}
+
+impl State {
+  fn next_id(&mut self) -> u64 {
+    self.last_id += 1;
+    self.last_id
+  }
+}
+
+/// Repository, which keeps everything in process memory and loses it on restart.
+///
+/// Ids are sequential numbers, so items are found in the order of creation as in mongo.
+#[derive(Default)]
+pub struct MemoryRepository {
+  state: Mutex<State>,
+}
+
+impl MemoryRepository {
+  fn state(&self) -> Result<std::sync::MutexGuard<'_, State>, ApiError> {
+    self
+      .state
+      .lock()
+      .map_err(|_| ApiError("Memory repository is poisoned".into()))
+  }
+}
+
+fn parse_id(id: &str) -> Option<u64> {
+  id.parse().ok()
+}
+
+fn titles(item: &FoodItemInner) -> impl Iterator<Item = &str> {
+  let fundamental = item.fundamental.as_ref().map(|f| f.title.as_str());
+  let branded = item.branded.as_ref().map(|b| b.title.as_str());
+  let composite = item.composite.as_ref().map(|c| c.title.as_str());
+  fundamental.into_iter().chain(branded).chain(composite)
+}
+
+fn matches(search: &FoodItemSearch, item: &FoodItemInner) -> bool {
+  let title_matches = match search.title.as_ref().filter(|t| !t.trim().is_empty()) {
+    Some(title) => {
+      let title = title.to_lowercase();
+      titles(item).any(|t| {
+        let t = t.to_lowercase();
+        let words = t.split_whitespace().collect::<Vec<_>>();
+        title.split_whitespace().all(|word| words.contains(&word))
+      })
+    }
+    None => true,
+  };
+  let barcode_matches = match &search.barcode {
+    Some(barcode) => item
+      .branded
+      .as_ref()
+      .is_some_and(|branded| branded.barcode == *barcode),
+    None => true,
+  };
+  title_matches && barcode_matches
+}
+
+#[async_trait]
+impl Repository for MemoryRepository {
+  async fn insert_user(&self, user: UserRecord) -> Result<String, ApiError> {
+    let mut state = self.state()?;
+    let id = state.next_id();
+    state.users.insert(id, user);
+    Ok(id.to_string())
+  }
+
+  async fn user_by_token_hash(&self, token_hash: &str) -> Result<Option<String>, ApiError> {
+    let state = self.state()?;
+    Ok(
+      state
+        .users
+        .iter()
+        .find(|(_, user)| user.token_hash == token_hash)
+        .map(|(id, _)| id.to_string()),
+    )
+  }
+
+  async fn insert_food_item(&self, item: FoodItemInner) -> Result<String, ApiError> {
+    let mut state = self.state()?;
+    let id = state.next_id();
+    state.food_items.insert(id, item);
+    Ok(id.to_string())
+  }
+
+  async fn upsert_branded_food_item(&self, item: BrandedFoodItem) -> Result<bool, ApiError> {
+    let mut state = self.state()?;
+    let existing = state.food_items.iter().find_map(|(id, stored)| {
+      stored
+        .branded
+        .as_ref()
+        .filter(|branded| branded.barcode == item.barcode)
+        .map(|_| *id)
+    });
+    let id = match existing {
+      Some(id) => id,
+      None => state.next_id(),
+    };
+    let item = FoodItemInner {
+      fundamental: None,
+      composite: None,
+      branded: Some(item),
+    };
+    Ok(state.food_items.insert(id, item).is_none())
+  }
+
+  async fn food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError> {
+    let state = self.state()?;
+    Ok(parse_id(id).and_then(|id| state.food_items.get(&id).cloned()))
+  }
+
+  async fn update_food_item(&self, id: &str, item: FoodItemInner) -> Result<bool, ApiError> {
+    let mut state = self.state()?;
+    match parse_id(id).and_then(|id| state.food_items.get_mut(&id)) {
+      Some(stored) => {
+        *stored = item;
+        Ok(true)
+      }
+      None => Ok(false),
+    }
+  }
+
+  async fn remove_food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError> {
+    let mut state = self.state()?;
+    Ok(parse_id(id).and_then(|id| state.food_items.remove(&id)))
+  }
+
+  async fn composites_containing(&self, id: &str) -> Result<Vec<String>, ApiError> {
+    let state = self.state()?;
+    Ok(
+      state
+        .food_items
+        .iter()
+        .filter(|(_, item)| {
+          item
+            .composite
+            .iter()
+            .flat_map(|composite| composite.composition.iter())
+            .any(|calculated| calculated.food_item.id == id)
+        })
+        .map(|(composite_id, _)| composite_id.to_string())
+        .collect(),
+    )
+  }
+
+  async fn search_food_items(
+    &self,
+    search: &FoodItemSearch,
+    skip: u64,
+    limit: u64,
+  ) -> Result<Vec<(String, FoodItemInner)>, ApiError> {
+    let state = self.state()?;
+    Ok(
+      state
+        .food_items
+        .iter()
+        .filter(|(_, item)| matches(search, item))
+        .skip(skip as usize)
+        .take(limit as usize)
+        .map(|(id, item)| (id.to_string(), item.clone()))
+        .collect(),
+    )
+  }
+
+  async fn insert_meal(&self, meal: MealRecord) -> Result<String, ApiError> {
+    let mut state = self.state()?;
+    let id = state.next_id();
+    state.meals.insert(id, meal);
+    Ok(id.to_string())
+  }
+
+  async fn meals(
+    &self,
+    user_id: &str,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+  ) -> Result<Vec<(String, MealRecord)>, ApiError> {
+    let state = self.state()?;
+    let mut meals = state
+      .meals
+      .iter()
+      .filter(|(_, meal)| meal.user_id == user_id)
+      .filter(|(_, meal)| start_time.is_none_or(|start| meal.consume_time >= start))
+      .filter(|(_, meal)| end_time.is_none_or(|end| meal.consume_time < end))
+      .map(|(id, meal)| (id.to_string(), meal.clone()))
+      .collect::<Vec<_>>();
+    meals.sort_by_key(|(_, meal)| meal.consume_time);
+    Ok(meals)
+  }
+
+  async fn count_meals_with_food_item(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<u64, ApiError> {
+    let state = self.state()?;
+    Ok(
+      state
+        .meals
+        .values()
+        .filter(|meal| meal.user_id == user_id && meal.food_item_id == food_item_id)
+        .count() as u64,
+    )
+  }
+
+  async fn food_item_in_meals_of_others(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<bool, ApiError> {
+    let state = self.state()?;
+    Ok(
+      state
+        .meals
+        .values()
+        .any(|meal| meal.user_id != user_id && meal.food_item_id == food_item_id),
+    )
+  }
+
+  async fn remove_meals_with_food_item(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<u64, ApiError> {
+    let mut state = self.state()?;
+    let before = state.meals.len();
+    state
+      .meals
+      .retain(|_, meal| meal.user_id != user_id || meal.food_item_id != food_item_id);
+    Ok((before - state.meals.len()) as u64)
+  }
+
+  async fn remove_meal(&self, user_id: &str, id: &str) -> Result<Option<MealRecord>, ApiError> {
+    let mut state = self.state()?;
+    let id = match parse_id(id) {
+      Some(id) => id,
+      None => return Ok(None),
+    };
+    if state.meals.get(&id).is_some_and(|meal| meal.user_id == user_id) {
+      Ok(state.meals.remove(&id))
+    } else {
+      Ok(None)
+    }
+  }
+}
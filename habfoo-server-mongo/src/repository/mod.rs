@@ -0,0 +1,114 @@
+//! Storage of users, food items and meals.
+//!
+//! [`HabFooApi`](crate::api_impl::HabFooApi) works with any [`Repository`]: [`MongoRepository`]
+//! keeps data in mongo, [`MemoryRepository`] keeps it in process for tests and demos.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use swagger::ApiError;
+
+use crate::types::{AddMealRequestTag, BrandedFoodItem, FoodItemInner};
+
+mod memory;
+mod mongo;
+
+pub use memory::MemoryRepository;
+pub use mongo::MongoRepository;
+
+/// User, as repositories store it. Token itself is given to the user once, only its hash is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserRecord {
+  pub name: String,
+  pub token_hash: String,
+}
+
+/// Meal, as repositories store it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MealRecord {
+  /// Id of the user, who consumed the meal. Meals are never visible to other users.
+  pub user_id: String,
+  pub consume_time: DateTime<Utc>,
+  pub food_item_id: String,
+  pub amount: f64,
+  pub tag: Option<AddMealRequestTag>,
+}
+
+/// Food items matching all given criteria are found, criteria which are `None` match anything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FoodItemSearch {
+  /// Words of title of any kind of food item. Items with all the words are found, case is ignored.
+  pub title: Option<String>,
+  /// Exact barcode of branded food item.
+  pub barcode: Option<String>,
+}
+
+#[async_trait]
+pub trait Repository: Send + Sync + 'static {
+  /// Stores user and returns its new id.
+  async fn insert_user(&self, user: UserRecord) -> Result<String, ApiError>;
+
+  /// Id of the user with given token hash.
+  async fn user_by_token_hash(&self, token_hash: &str) -> Result<Option<String>, ApiError>;
+
+  /// Stores food item and returns its new id.
+  async fn insert_food_item(&self, item: FoodItemInner) -> Result<String, ApiError>;
+
+  /// Stores branded item in place of the one with the same barcode, so its id and links to it
+  /// are kept. Returns `true` if there was no such item and the new one is created.
+  async fn upsert_branded_food_item(&self, item: BrandedFoodItem) -> Result<bool, ApiError>;
+
+  async fn food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError>;
+
+  /// Replaces food item data. Returns `false` if there is no such item.
+  async fn update_food_item(&self, id: &str, item: FoodItemInner) -> Result<bool, ApiError>;
+
+  /// Removes food item and returns it, `None` if there was no such item.
+  async fn remove_food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError>;
+
+  /// Ids of composite food items, which have item `id` right in their composition.
+  async fn composites_containing(&self, id: &str) -> Result<Vec<String>, ApiError>;
+
+  /// Found items with their ids, in the order of creation.
+  async fn search_food_items(
+    &self,
+    search: &FoodItemSearch,
+    skip: u64,
+    limit: u64,
+  ) -> Result<Vec<(String, FoodItemInner)>, ApiError>;
+
+  /// Stores meal and returns its new id.
+  async fn insert_meal(&self, meal: MealRecord) -> Result<String, ApiError>;
+
+  /// Meals of the user consumed from `start_time` (inclusive) to `end_time` (exclusive), oldest
+  /// first.
+  async fn meals(
+    &self,
+    user_id: &str,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+  ) -> Result<Vec<(String, MealRecord)>, ApiError>;
+
+  /// Amount of meals of the user with the food item.
+  async fn count_meals_with_food_item(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<u64, ApiError>;
+
+  /// Meals of any user, other than the given one, have the food item.
+  async fn food_item_in_meals_of_others(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<bool, ApiError>;
+
+  /// Removes meals of the user with the food item, returns amount of removed meals.
+  async fn remove_meals_with_food_item(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<u64, ApiError>;
+
+  /// Removes meal of the user and returns it, `None` if the user has no such meal.
+  async fn remove_meal(&self, user_id: &str, id: &str) -> Result<Option<MealRecord>, ApiError>;
+}
@@ -0,0 +1,496 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::TryStreamExt;
+use log::info;
+use mongodb::{
+  bson::{self, doc, oid::ObjectId, Document},
+  options::{FindOneAndReplaceOptions, FindOneOptions, FindOptions, IndexOptions},
+  IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use swagger::ApiError;
+
+use super::{FoodItemSearch, MealRecord, Repository, UserRecord};
+use crate::types::{AddMealRequestTag, BrandedFoodItem, FoodItemInner};
+
+/// User as it is stored in the `users` collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UserDocument {
+  #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+  id: Option<ObjectId>,
+  name: String,
+  #[serde(rename = "tokenHash")]
+  token_hash: String,
+}
+
+/// Meal as it is stored in the `meals` collection.
+///
+/// Consume time is kept as a BSON date, so range queries and sorting are done by mongo itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MealDocument {
+  #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+  id: Option<ObjectId>,
+  #[serde(rename = "userId")]
+  user_id: String,
+  #[serde(rename = "consumeTime")]
+  consume_time: bson::DateTime,
+  #[serde(rename = "foodItemId")]
+  food_item_id: String,
+  amount: f64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  tag: Option<AddMealRequestTag>,
+}
+
+impl From<MealRecord> for MealDocument {
+  fn from(meal: MealRecord) -> Self {
+    MealDocument {
+      id: None,
+      user_id: meal.user_id,
+      consume_time: to_bson_time(&meal.consume_time),
+      food_item_id: meal.food_item_id,
+      amount: meal.amount,
+      tag: meal.tag,
+    }
+  }
+}
+
+impl From<MealDocument> for MealRecord {
+  fn from(document: MealDocument) -> Self {
+    MealRecord {
+      user_id: document.user_id,
+      consume_time: from_bson_time(&document.consume_time),
+      food_item_id: document.food_item_id,
+      amount: document.amount,
+      tag: document.tag,
+    }
+  }
+}
+
+/// Food item as it is stored in the `foodItems` collection, together with its mongo id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FoodItemDocument {
+  #[serde(rename = "_id")]
+  id: ObjectId,
+  #[serde(flatten)]
+  item: FoodItemInner,
+}
+
+/// Title is searched through the text index. Every word is quoted, so items with all the words
+/// are found rather than with any of them, as they are in memory repository.
+fn search_filter(search: &FoodItemSearch) -> Document {
+  let mut filter = Document::new();
+  if let Some(title) = search.title.as_ref().filter(|t| !t.trim().is_empty()) {
+    let words = title
+      .split_whitespace()
+      .map(|word| format!("\"{}\"", word.replace('"', "")))
+      .collect::<Vec<_>>()
+      .join(" ");
+    filter.insert("$text", doc! { "$search": words });
+  }
+  if let Some(barcode) = &search.barcode {
+    filter.insert("branded.barcode", barcode);
+  }
+  filter
+}
+
+fn to_bson_time(time: &DateTime<Utc>) -> bson::DateTime {
+  bson::DateTime::from_millis(time.timestamp_millis())
+}
+
+fn from_bson_time(time: &bson::DateTime) -> DateTime<Utc> {
+  Utc.timestamp_millis(time.timestamp_millis())
+}
+
+fn inserted_id(result: mongodb::results::InsertOneResult) -> Result<String, ApiError> {
+  result
+    .inserted_id
+    .as_object_id()
+    .map(|id| id.to_hex())
+    .ok_or_else(|| ApiError("Document is inserted with unexpected id".into()))
+}
+
+fn mongo_error(mongo_err: mongodb::error::Error) -> ApiError {
+  ApiError(format!("{mongo_err}"))
+}
+
+#[derive(Clone)]
+pub struct MongoRepository {
+  database_client: mongodb::Client,
+}
+
+impl MongoRepository {
+  pub async fn new(mongodb_uri: &str) -> Self {
+    let database_client = mongodb::Client::with_uri_str(mongodb_uri)
+      .await
+      .unwrap_or_else(|_| panic!("Cannot create mongodb client with addr {}", mongodb_uri));
+    info!("Good");
+    let repository = MongoRepository { database_client };
+    repository
+      .create_indexes()
+      .await
+      .unwrap_or_else(|err| panic!("Cannot create indexes: {}", err.0));
+    repository
+  }
+
+  /// Text index over titles serves search, unique barcode index serves exact lookups of branded
+  /// items and keeps import from storing one product twice. Users are found by unique token hash,
+  /// meals are fetched per user by consume time. References to food items from composites and
+  /// meals are indexed to check them before deletion.
+  async fn create_indexes(&self) -> Result<(), ApiError> {
+    let token_index = IndexModel::builder()
+      .keys(doc! { "tokenHash": 1 })
+      .options(
+        IndexOptions::builder()
+          .name("token_hash".to_string())
+          .unique(true)
+          .build(),
+      )
+      .build();
+    self
+      .get_user_collection()
+      .await?
+      .create_index(token_index, None)
+      .await
+      .map_err(mongo_error)?;
+    let user_meals_index = IndexModel::builder()
+      .keys(doc! { "userId": 1, "consumeTime": 1 })
+      .options(IndexOptions::builder().name("user_consume_time".to_string()).build())
+      .build();
+    let food_item_meals_index = IndexModel::builder()
+      .keys(doc! { "foodItemId": 1 })
+      .options(IndexOptions::builder().name("food_item_id".to_string()).build())
+      .build();
+    self
+      .get_meal_collection()
+      .await?
+      .create_indexes([user_meals_index, food_item_meals_index], None)
+      .await
+      .map_err(mongo_error)?;
+
+    let collection = self.get_food_item_collection().await?;
+    // Titles are not stemmed, so words match as they are written, only case is ignored.
+    let title_index = IndexModel::builder()
+      .keys(doc! {
+        "fundamental.title": "text",
+        "branded.title": "text",
+        "composite.title": "text",
+      })
+      .options(
+        IndexOptions::builder()
+          .name("titles".to_string())
+          .default_language("none".to_string())
+          .build(),
+      )
+      .build();
+    let barcode_index = IndexModel::builder()
+      .keys(doc! { "branded.barcode": 1 })
+      .options(
+        IndexOptions::builder()
+          .name("branded_barcode".to_string())
+          .unique(true)
+          .sparse(true)
+          .build(),
+      )
+      .build();
+    let composition_index = IndexModel::builder()
+      .keys(doc! { "composite.composition.foodItem.id": 1 })
+      .options(
+        IndexOptions::builder()
+          .name("composition_food_item_id".to_string())
+          .sparse(true)
+          .build(),
+      )
+      .build();
+    collection
+      .create_indexes([title_index, barcode_index, composition_index], None)
+      .await
+      .map_err(mongo_error)?;
+    Ok(())
+  }
+
+  async fn get_collection<T>(&self, name: &str) -> Result<mongodb::Collection<T>, ApiError> {
+    self
+      .database_client
+      .default_database()
+      .map(|db| db.collection(name))
+      .ok_or_else(|| ApiError("Cannot get collection".into()))
+  }
+
+  async fn get_food_item_collection(
+    &self,
+  ) -> Result<mongodb::Collection<FoodItemInner>, ApiError> {
+    self.get_collection("foodItems").await
+  }
+
+  async fn get_food_item_document_collection(
+    &self,
+  ) -> Result<mongodb::Collection<FoodItemDocument>, ApiError> {
+    self.get_collection("foodItems").await
+  }
+
+  async fn get_user_collection(&self) -> Result<mongodb::Collection<UserDocument>, ApiError> {
+    self.get_collection("users").await
+  }
+
+  async fn get_meal_collection(&self) -> Result<mongodb::Collection<MealDocument>, ApiError> {
+    self.get_collection("meals").await
+  }
+}
+
+#[async_trait]
+impl Repository for MongoRepository {
+  async fn insert_user(&self, user: UserRecord) -> Result<String, ApiError> {
+    let document = UserDocument {
+      id: None,
+      name: user.name,
+      token_hash: user.token_hash,
+    };
+    let result = self
+      .get_user_collection()
+      .await?
+      .insert_one(document, None)
+      .await
+      .map_err(mongo_error)?;
+    inserted_id(result)
+  }
+
+  async fn user_by_token_hash(&self, token_hash: &str) -> Result<Option<String>, ApiError> {
+    self
+      .get_user_collection()
+      .await?
+      .find_one(doc! { "tokenHash": token_hash }, FindOneOptions::default())
+      .await
+      .map(|found| found.and_then(|document| document.id).map(|id| id.to_hex()))
+      .map_err(mongo_error)
+  }
+
+  async fn insert_food_item(&self, item: FoodItemInner) -> Result<String, ApiError> {
+    let result = self
+      .get_food_item_collection()
+      .await?
+      .insert_one(item, None)
+      .await
+      .map_err(mongo_error)?;
+    inserted_id(result)
+  }
+
+  async fn upsert_branded_food_item(&self, item: BrandedFoodItem) -> Result<bool, ApiError> {
+    let filter = doc! { "branded.barcode": &item.barcode };
+    let item = FoodItemInner {
+      fundamental: None,
+      composite: None,
+      branded: Some(item),
+    };
+    let options = FindOneAndReplaceOptions::builder().upsert(true).build();
+    // Document before replacement is returned, there is none if it is inserted.
+    self
+      .get_food_item_collection()
+      .await?
+      .find_one_and_replace(filter, item, options)
+      .await
+      .map(|replaced| replaced.is_none())
+      .map_err(mongo_error)
+  }
+
+  async fn food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError> {
+    // Id, which is not an object id, can't belong to any stored item.
+    let id = match ObjectId::parse_str(id) {
+      Ok(id) => id,
+      Err(_) => return Ok(None),
+    };
+    self
+      .get_food_item_document_collection()
+      .await?
+      .find_one(doc! { "_id": id }, FindOneOptions::default())
+      .await
+      .map(|found| found.map(|document| document.item))
+      .map_err(mongo_error)
+  }
+
+  async fn update_food_item(&self, id: &str, item: FoodItemInner) -> Result<bool, ApiError> {
+    let id = match ObjectId::parse_str(id) {
+      Ok(id) => id,
+      Err(_) => return Ok(false),
+    };
+    self
+      .get_food_item_collection()
+      .await?
+      .replace_one(doc! { "_id": id }, item, None)
+      .await
+      .map(|result| result.matched_count > 0)
+      .map_err(mongo_error)
+  }
+
+  async fn remove_food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError> {
+    let id = match ObjectId::parse_str(id) {
+      Ok(id) => id,
+      Err(_) => return Ok(None),
+    };
+    self
+      .get_food_item_document_collection()
+      .await?
+      .find_one_and_delete(doc! { "_id": id }, None)
+      .await
+      .map(|found| found.map(|document| document.item))
+      .map_err(mongo_error)
+  }
+
+  async fn composites_containing(&self, id: &str) -> Result<Vec<String>, ApiError> {
+    self
+      .get_food_item_document_collection()
+      .await?
+      .find(doc! { "composite.composition.foodItem.id": id }, None)
+      .await
+      .map_err(mongo_error)?
+      .map_ok(|document| document.id.to_hex())
+      .try_collect()
+      .await
+      .map_err(mongo_error)
+  }
+
+  async fn search_food_items(
+    &self,
+    search: &FoodItemSearch,
+    skip: u64,
+    limit: u64,
+  ) -> Result<Vec<(String, FoodItemInner)>, ApiError> {
+    let options = FindOptions::builder()
+      .sort(doc! { "_id": 1 })
+      .skip(skip)
+      .limit(limit as i64)
+      .build();
+    self
+      .get_food_item_document_collection()
+      .await?
+      .find(search_filter(search), options)
+      .await
+      .map_err(mongo_error)?
+      .map_ok(|document| (document.id.to_hex(), document.item))
+      .try_collect()
+      .await
+      .map_err(mongo_error)
+  }
+
+  async fn insert_meal(&self, meal: MealRecord) -> Result<String, ApiError> {
+    let result = self
+      .get_meal_collection()
+      .await?
+      .insert_one(MealDocument::from(meal), None)
+      .await
+      .map_err(mongo_error)?;
+    inserted_id(result)
+  }
+
+  async fn meals(
+    &self,
+    user_id: &str,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+  ) -> Result<Vec<(String, MealRecord)>, ApiError> {
+    let mut consume_time = Document::new();
+    if let Some(start_time) = start_time {
+      consume_time.insert("$gte", to_bson_time(&start_time));
+    }
+    if let Some(end_time) = end_time {
+      consume_time.insert("$lt", to_bson_time(&end_time));
+    }
+    let mut filter = doc! { "userId": user_id };
+    if !consume_time.is_empty() {
+      filter.insert("consumeTime", consume_time);
+    }
+    let options = FindOptions::builder()
+      .sort(doc! { "consumeTime": 1 })
+      .build();
+
+    self
+      .get_meal_collection()
+      .await?
+      .find(filter, options)
+      .await
+      .map_err(mongo_error)?
+      .map_ok(|document| {
+        let id = document.id.map(|id| id.to_hex()).unwrap_or_default();
+        (id, MealRecord::from(document))
+      })
+      .try_collect()
+      .await
+      .map_err(mongo_error)
+  }
+
+  async fn count_meals_with_food_item(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<u64, ApiError> {
+    self
+      .get_meal_collection()
+      .await?
+      .count_documents(doc! { "userId": user_id, "foodItemId": food_item_id }, None)
+      .await
+      .map_err(mongo_error)
+  }
+
+  async fn food_item_in_meals_of_others(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<bool, ApiError> {
+    self
+      .get_meal_collection()
+      .await?
+      .find_one(
+        doc! { "userId": { "$ne": user_id }, "foodItemId": food_item_id },
+        None,
+      )
+      .await
+      .map(|meal| meal.is_some())
+      .map_err(mongo_error)
+  }
+
+  async fn remove_meals_with_food_item(
+    &self,
+    user_id: &str,
+    food_item_id: &str,
+  ) -> Result<u64, ApiError> {
+    self
+      .get_meal_collection()
+      .await?
+      .delete_many(doc! { "userId": user_id, "foodItemId": food_item_id }, None)
+      .await
+      .map(|result| result.deleted_count)
+      .map_err(mongo_error)
+  }
+
+  async fn remove_meal(&self, user_id: &str, id: &str) -> Result<Option<MealRecord>, ApiError> {
+    let id = match ObjectId::parse_str(id) {
+      Ok(id) => id,
+      Err(_) => return Ok(None),
+    };
+    self
+      .get_meal_collection()
+      .await?
+      .find_one_and_delete(doc! { "_id": id, "userId": user_id }, None)
+      .await
+      .map(|found| found.map(MealRecord::from))
+      .map_err(mongo_error)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn searches_titles_by_all_quoted_words() {
+    let search = FoodItemSearch {
+      title: Some(" Chicken  \"egg\" ".into()),
+      barcode: None,
+    };
+
+    assert_eq!(
+      search_filter(&search),
+      doc! { "$text": { "$search": r#""Chicken" "egg""# } }
+    );
+    assert_eq!(search_filter(&FoodItemSearch::default()), Document::new());
+  }
+}
@@ -1,16 +1,143 @@
-use habfoo_api_generated::server::MakeService;
-use swagger::EmptyContext;
-use crate::api_impl::HabFooApi;
+use routerify::RouterService;
 
-pub async fn create(addr: &str, mongodb_addr: &str) {
-  let addr = addr.parse().expect("Failed to bind address");
+use crate::{api_impl::HabFooApi, router};
 
-  let server = HabFooApi::new(mongodb_addr).await;
-  let service = MakeService::new(server);
+pub async fn create(addr: &str, api: HabFooApi) {
+  let addr = addr.parse().expect("Failed to bind address");
 
-  let service = habfoo_api_generated::server::context::MakeAddContext::<_, EmptyContext> :: new(service);
+  let router = router::create_routing_table(api);
+  let service = RouterService::new(router).expect("Failed to create router service");
 
-  hyper::server::Server::bind(&addr).serve(service).await.unwrap()
+  hyper::server::Server::bind(&addr)
+    .serve(service)
+    .await
+    .unwrap()
 }
 
+#[cfg(test)]
+mod tests {
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::{
+    client::Client,
+    repository::MemoryRepository,
+    traits::{
+      AddMealResultType, CreateFoodItemResultType, CreateUserResultType, Credentials,
+      GetFoodItemResultType,
+    },
+    types::{
+      AddMealRequest, CreateUserRequest, FetchMealsRequest, FoodItemInner, FundamentalFoodItem,
+    },
+  };
+
+  /// Base url of the api served on a free local port
+  fn serve() -> String {
+    let router = router::create_routing_table(HabFooApi::new(MemoryRepository::default()));
+    let service = RouterService::new(router).unwrap();
+    let server = hyper::server::Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+  }
+
+  /// Token of newly registered user
+  async fn token(url: &str) -> String {
+    let created = Client::new(url.to_owned())
+      .create_user(CreateUserRequest {
+        name: "Alice".into(),
+      })
+      .await
+      .unwrap();
+    match created {
+      CreateUserResultType::Result_200(created) => created.token,
+      CreateUserResultType::Result_422(_) => panic!("Valid user is not created"),
+    }
+  }
+
+  #[tokio::test]
+  async fn client_calls_served_api() {
+    let url = serve();
+    let client = Client::new(url.clone());
+    let token = token(&url).await;
+    assert!(client.fetch_meals(None).await.is_err());
+    let refused = client
+      .create_user(CreateUserRequest { name: "".into() })
+      .await
+      .unwrap();
+    assert!(matches!(refused, CreateUserResultType::Result_422(_)));
 
+    let client = client.with_credentials(Credentials::BearerAuth(token));
+    let item = FoodItemInner {
+      fundamental: Some(FundamentalFoodItem {
+        title: "Egg".into(),
+        description: None,
+        nutrients: Vec::new(),
+      }),
+      composite: None,
+      branded: None,
+    };
+    let item_id = match client.create_food_item(item).await.unwrap() {
+      CreateFoodItemResultType::Result_200(created) => created.id,
+      CreateFoodItemResultType::Result_422(_) => panic!("Valid food item is not created"),
+    };
+    let consume_time = Utc.ymd(2022, 6, 1).and_hms(8, 0, 0);
+    let added = client
+      .add_meal(AddMealRequest {
+        consume_time,
+        food_item_id: item_id,
+        amount: 150.0,
+        tag: None,
+      })
+      .await
+      .unwrap();
+    assert!(matches!(added, AddMealResultType::Result_200(_)));
+    let fetched = client
+      .fetch_meals(Some(FetchMealsRequest {
+        start_time: Some(consume_time),
+        end_time: None,
+      }))
+      .await
+      .unwrap();
+    let meals = fetched.items.unwrap();
+    assert_eq!(meals.len(), 1);
+    assert_eq!(meals[0].amount, 150.0);
+    assert_eq!(meals[0].consume_time, consume_time);
+
+    let missing = client.get_food_item("unknown".into()).await.unwrap();
+    assert_eq!(missing, GetFoodItemResultType::Result_404);
+  }
+
+  #[tokio::test]
+  async fn rejects_meal_without_required_fields() {
+    let url = serve();
+    let token = token(&url).await;
+    let request = hyper::Request::post(format!("{url}/add-meal"))
+      .header("Authorization", format!("Bearer {token}"))
+      .header("Content-Type", "application/json")
+      .body(hyper::Body::from(r#"{"amount": 100}"#))
+      .unwrap();
+    let response = hyper::Client::new().request(request).await.unwrap();
+    assert_eq!(response.status(), hyper::StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn challenges_unauthenticated_requests() {
+    let url = serve();
+    let fetch_meals = |authorization: Option<String>| {
+      let mut request = hyper::Request::get(format!("{url}/fetch-meals"));
+      if let Some(authorization) = authorization {
+        request = request.header("Authorization", authorization);
+      }
+      hyper::Client::new().request(request.body(hyper::Body::empty()).unwrap())
+    };
+
+    let response = fetch_meals(None).await.unwrap();
+    assert_eq!(response.status(), hyper::StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["WWW-Authenticate"], "Bearer");
+
+    let token = token(&url).await;
+    let response = fetch_meals(Some(format!("bearer {token}"))).await.unwrap();
+    assert_eq!(response.status(), hyper::StatusCode::OK);
+  }
+}
@@ -0,0 +1,215 @@
+//! Nutrition summary over meals: nutrients consumed per day and per meal tag.
+
+use std::collections::{BTreeMap, HashMap};
+
+use swagger::ApiError;
+
+use crate::{
+  nutrients::{self, FoodItemSource, NutrientProfile},
+  types::{
+    DailyNutrition, Meal, NutrientAmount, NutrientKind, NutritionSummaryResponse, TagNutrition,
+  },
+};
+
+type NutrientTotals = BTreeMap<NutrientKind, f64>;
+
+#[derive(Default)]
+struct DayTotals {
+  total: NutrientTotals,
+  tags: BTreeMap<Option<String>, NutrientTotals>,
+}
+
+/// Sums nutrients of `meals` per UTC day. Each food item is rolled up once, however many meals
+/// refer to it. Meals, which food items are deleted or can't be rolled up, are skipped and listed
+/// in the summary.
+pub async fn summarize<S>(source: &S, meals: &[Meal]) -> Result<NutritionSummaryResponse, ApiError>
+where
+  S: FoodItemSource + Sync,
+{
+  let mut profiles: HashMap<&str, Option<NutrientProfile>> = HashMap::new();
+  let mut days: BTreeMap<_, DayTotals> = BTreeMap::new();
+  let mut skipped_meal_ids = Vec::new();
+
+  for meal in meals {
+    let food_item_id = meal.food_item_id.as_str();
+    if !profiles.contains_key(food_item_id) {
+      let items = nutrients::load_composition(source, food_item_id).await?;
+      let profile = nutrients::rollup_loaded(&items, food_item_id).ok();
+      profiles.insert(food_item_id, profile);
+    }
+    let profile = match &profiles[food_item_id] {
+      Some(profile) => profile,
+      None => {
+        skipped_meal_ids.extend(meal.id.clone());
+        continue;
+      }
+    };
+
+    let day = days
+      .entry(meal.consume_time.naive_utc().date())
+      .or_default();
+    let tag = day
+      .tags
+      .entry(meal.tag.map(|tag| tag.to_string()))
+      .or_default();
+    for (kind, consumed) in profile.amounts(meal.amount) {
+      *day.total.entry(kind).or_insert(0.0) += consumed;
+      *tag.entry(kind).or_insert(0.0) += consumed;
+    }
+  }
+
+  Ok(NutritionSummaryResponse {
+    days: days
+      .into_iter()
+      .map(|(date, totals)| DailyNutrition {
+        date,
+        total: into_amounts(totals.total),
+        tags: totals
+          .tags
+          .into_iter()
+          .map(|(tag, nutrients)| TagNutrition {
+            tag,
+            nutrients: into_amounts(nutrients),
+          })
+          .collect(),
+      })
+      .collect(),
+    skipped_meal_ids,
+  })
+}
+
+fn into_amounts(totals: NutrientTotals) -> Vec<NutrientAmount> {
+  totals
+    .into_iter()
+    .map(|(kind, amount)| NutrientAmount { amount, kind })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use async_trait::async_trait;
+  use chrono::{NaiveDate, TimeZone, Utc};
+
+  use super::*;
+  use crate::types::{
+    AddMealRequestTag, CalculatedFoodItem, CompositeFoodItem, FoodItemInner, FoodItemLink,
+    FoodItemLinkType, FundamentalFoodItem, Nutrient,
+  };
+
+  struct Items(HashMap<String, FoodItemInner>);
+
+  #[async_trait]
+  impl FoodItemSource for Items {
+    async fn food_item(&self, id: &str) -> Result<Option<FoodItemInner>, ApiError> {
+      Ok(self.0.get(id).cloned())
+    }
+  }
+
+  fn fundamental(protein: f64) -> FoodItemInner {
+    FoodItemInner {
+      fundamental: Some(FundamentalFoodItem {
+        title: "fundamental".into(),
+        description: None,
+        nutrients: vec![Nutrient {
+          percentage: protein,
+          kind: NutrientKind::Protein,
+        }],
+      }),
+      composite: None,
+      branded: None,
+    }
+  }
+
+  fn composite(id: &str) -> FoodItemInner {
+    FoodItemInner {
+      fundamental: None,
+      composite: Some(CompositeFoodItem {
+        title: "composite".into(),
+        description: None,
+        composition: vec![CalculatedFoodItem {
+          amount: 100.0,
+          food_item: FoodItemLink {
+            title: id.into(),
+            brand: None,
+            r#type: FoodItemLinkType::Fundamental,
+            id: id.into(),
+          },
+        }],
+      }),
+      branded: None,
+    }
+  }
+
+  fn items() -> Items {
+    Items(
+      [
+        ("egg".to_owned(), fundamental(12.0)),
+        ("milk".to_owned(), fundamental(3.0)),
+        ("pancake".to_owned(), composite("deleted")),
+      ]
+      .into(),
+    )
+  }
+
+  fn meal(id: &str, food_item_id: &str, day: u32, tag: Option<AddMealRequestTag>) -> Meal {
+    Meal {
+      id: Some(id.into()),
+      consume_time: Utc.ymd(2022, 6, day).and_hms(8, 0, 0),
+      food_item_id: food_item_id.into(),
+      amount: 200.0,
+      tag,
+    }
+  }
+
+  fn protein(amounts: &[NutrientAmount]) -> f64 {
+    amounts
+      .iter()
+      .find(|amount| amount.kind == NutrientKind::Protein)
+      .map(|amount| amount.amount)
+      .unwrap_or(0.0)
+  }
+
+  #[tokio::test]
+  async fn sums_nutrients_per_day_and_tag() {
+    let meals = [
+      meal("1", "egg", 1, Some(AddMealRequestTag::Breakfast)),
+      meal("2", "milk", 1, None),
+      meal("3", "egg", 2, None),
+    ];
+
+    let summary = summarize(&items(), &meals).await.unwrap();
+    let dates = summary.days.iter().map(|day| day.date).collect::<Vec<_>>();
+    assert_eq!(
+      dates,
+      vec![
+        NaiveDate::from_ymd(2022, 6, 1),
+        NaiveDate::from_ymd(2022, 6, 2)
+      ]
+    );
+    let first = &summary.days[0];
+    assert!((protein(&first.total) - 30.0).abs() < 1e-9);
+    let tags = first
+      .tags
+      .iter()
+      .map(|tag| tag.tag.clone())
+      .collect::<Vec<_>>();
+    assert_eq!(tags, vec![None, Some("breakfast".to_owned())]);
+    assert!((protein(&first.tags[1].nutrients) - 24.0).abs() < 1e-9);
+    assert!((protein(&summary.days[1].total) - 24.0).abs() < 1e-9);
+    assert!(summary.skipped_meal_ids.is_empty());
+  }
+
+  #[tokio::test]
+  async fn skips_meals_with_deleted_food_items() {
+    let meals = [
+      meal("1", "egg", 1, None),
+      meal("2", "deleted", 1, None),
+      meal("3", "pancake", 1, None),
+    ];
+
+    let summary = summarize(&items(), &meals).await.unwrap();
+    assert_eq!(summary.skipped_meal_ids, vec!["2", "3"]);
+    assert_eq!(summary.days.len(), 1);
+    assert!((protein(&summary.days[0].total) - 24.0).abs() < 1e-9);
+  }
+}
@@ -0,0 +1,191 @@
+//! Validation of food items and meals before they are stored.
+//!
+//! Every problem is reported as a violation with JSON pointer to the invalid part of the item, so
+//! client is able to show all of them at once.
+
+use std::collections::{HashMap, HashSet};
+
+use swagger::ApiError;
+
+use crate::{
+  nutrients::{self, FoodItemSource, RollupError},
+  repository::MealRecord,
+  types::{
+    CalculatedFoodItem, CreateUserRequest, FoodItemInner, Nutrient, NutrientKind,
+    ValidationViolation,
+  },
+};
+
+#[derive(Default)]
+struct Violations(Vec<ValidationViolation>);
+
+impl Violations {
+  fn push(&mut self, path: &str, message: impl Into<String>) {
+    self.0.push(ValidationViolation {
+      path: path.to_owned(),
+      message: message.into(),
+    });
+  }
+
+  fn require_text(&mut self, path: &str, text: &str) {
+    if text.trim().is_empty() {
+      self.push(path, "must not be empty");
+    }
+  }
+
+  fn check_nutrients(&mut self, path: &str, nutrients: &[Nutrient]) {
+    let mut kinds = HashSet::new();
+    for (index, nutrient) in nutrients.iter().enumerate() {
+      // Energy is kcal per 100 g, the spec of nutrient bounds only mass shares by 100.
+      if nutrient.kind == NutrientKind::Energy {
+        if nutrient.percentage < 0.0 {
+          self.push(
+            &format!("{path}/{index}/percentage"),
+            format!("must not be negative, got {}", nutrient.percentage),
+          );
+        }
+      } else if !(0.0..=100.0).contains(&nutrient.percentage) {
+        self.push(
+          &format!("{path}/{index}/percentage"),
+          format!("must be within 0 and 100, got {}", nutrient.percentage),
+        );
+      }
+      if !kinds.insert(nutrient.kind) {
+        self.push(
+          &format!("{path}/{index}/kind"),
+          format!("{} is listed more than once", nutrient.kind),
+        );
+      }
+    }
+  }
+
+  async fn check_composition<S>(
+    &mut self,
+    source: &S,
+    path: &str,
+    composition: &[CalculatedFoodItem],
+  ) -> Result<(), ApiError>
+  where
+    S: FoodItemSource + Sync,
+  {
+    if composition.is_empty() {
+      self.push(path, "must contain at least one food item");
+    }
+    for (index, calculated) in composition.iter().enumerate() {
+      if calculated.amount <= 0.0 {
+        self.push(
+          &format!("{path}/{index}/amount"),
+          format!("must be positive, got {}", calculated.amount),
+        );
+      }
+      let id = &calculated.food_item.id;
+      if source.food_item(id).await?.is_none() {
+        self.push(
+          &format!("{path}/{index}/foodItem/id"),
+          format!("food item {id} does not exist"),
+        );
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Problems of the new user, empty if it can be registered.
+pub fn validate_user(user: &CreateUserRequest) -> Vec<ValidationViolation> {
+  let mut violations = Violations::default();
+  violations.require_text("/name", &user.name);
+  violations.0
+}
+
+/// Problems of the food item, empty if it can be stored.
+pub async fn validate_food_item<S>(
+  source: &S,
+  item: &FoodItemInner,
+) -> Result<Vec<ValidationViolation>, ApiError>
+where
+  S: FoodItemSource + Sync,
+{
+  let mut violations = Violations::default();
+  let kinds = [
+    item.fundamental.is_some(),
+    item.composite.is_some(),
+    item.branded.is_some(),
+  ];
+  if kinds.iter().filter(|is_set| **is_set).count() != 1 {
+    violations.push(
+      "",
+      "exactly one of fundamental, composite or branded must be set",
+    );
+  }
+
+  if let Some(fundamental) = &item.fundamental {
+    violations.require_text("/fundamental/title", &fundamental.title);
+    violations.check_nutrients("/fundamental/nutrients", &fundamental.nutrients);
+  }
+  if let Some(branded) = &item.branded {
+    violations.require_text("/branded/title", &branded.title);
+    violations.require_text("/branded/brand", &branded.brand);
+    violations.require_text("/branded/barcode", &branded.barcode);
+    violations.check_nutrients("/branded/nutrients", &branded.nutrients);
+  }
+  if let Some(composite) = &item.composite {
+    violations.require_text("/composite/title", &composite.title);
+    violations
+      .check_composition(source, "/composite/composition", &composite.composition)
+      .await?;
+  }
+  Ok(violations.0)
+}
+
+/// Problems of the meal, empty if it can be stored.
+pub async fn validate_meal<S>(
+  source: &S,
+  meal: &MealRecord,
+) -> Result<Vec<ValidationViolation>, ApiError>
+where
+  S: FoodItemSource + Sync,
+{
+  let mut violations = Violations::default();
+  let id = &meal.food_item_id;
+  if source.food_item(id).await?.is_none() {
+    violations.push("/foodItemId", format!("food item {id} does not exist"));
+  }
+  Ok(violations.0)
+}
+
+/// Problems of the food item, which replaces item `id`. Besides everything checked for new items,
+/// its composition must not lead back to the item itself.
+pub async fn validate_food_item_update<S>(
+  source: &S,
+  id: &str,
+  item: &FoodItemInner,
+) -> Result<Vec<ValidationViolation>, ApiError>
+where
+  S: FoodItemSource + Sync,
+{
+  let mut violations = Violations(validate_food_item(source, item).await?);
+  if let Some(composite) = &item.composite {
+    let mut items = HashMap::new();
+    for calculated in composite.composition.iter() {
+      items.extend(nutrients::load_composition(source, &calculated.food_item.id).await?);
+    }
+    items.insert(id.to_owned(), item.clone());
+    if let Err(err @ RollupError::Cycle(_)) = nutrients::rollup_loaded(&items, id) {
+      violations.0.push(composition_violation(&err));
+    }
+  }
+  Ok(violations.0)
+}
+
+/// Problem of the composition, which doesn't let the item be rolled up into nutrients.
+pub fn composition_violation(err: &RollupError) -> ValidationViolation {
+  let message = match err {
+    RollupError::Cycle(ids) => format!("makes a cycle: {}", ids.join(" -> ")),
+    RollupError::MissingItem(id) => format!("food item {id} does not exist"),
+    RollupError::EmptyItem(id) => format!("food item {id} has no data"),
+  };
+  ValidationViolation {
+    path: "/composite/composition".to_owned(),
+    message,
+  }
+}
@@ -1,4 +1,4 @@
-#![allow(dead_code, unused_variables)]
+#![allow(dead_code, unused_variables, clippy::approx_constant)]
 
 use http::Method;
 use serde_json::json;
@@ -49,13 +49,13 @@ impl FromRef for Example {
 
         match refpath {
             Ref::Path(path) => read_from_file(spec, path),
-            Ref::InFile { source, kind, name } => match kind {
+            Ref::InFile { kind, name, .. } => match kind {
                 RefType::Example => spec
                     .components
                     .as_ref()
                     .and_then(|cs| cs.examples.get(&name))
                     .ok_or_else(|| RefError::Unresolvable(path.to_owned()))
-                    .and_then(|oor| oor.resolve(&spec)),
+                    .and_then(|oor| oor.resolve(spec)),
 
                 typ => Err(RefError::MismatchedType(typ, RefType::Example)),
             },
@@ -2,7 +2,12 @@
 //!
 //! High-level structures include [`Spec`], [`Components`] & [`Schema`].
 
-use std::{collections::BTreeMap, iter::Iterator};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    iter::Iterator,
+    path::PathBuf,
+};
 
 use derive_more::Error;
 use http::Method;
@@ -11,6 +16,7 @@ use serde::{Deserialize, Serialize};
 
 mod components;
 mod contact;
+mod discriminator;
 mod encoding;
 
 mod error;
@@ -41,6 +47,7 @@ pub(super) use read_from_file::read_from_file;
 
 pub use components::*;
 pub use contact::*;
+pub use discriminator::*;
 pub use encoding::*;
 pub use example::*;
 pub use external_doc::*;
@@ -72,6 +79,11 @@ pub struct Spec {
 
     #[serde(default, skip_serializing, skip_deserializing)]
     pub root_directory: Option<String>,
+
+    /// Files, references were read from, see [`Spec::read_files`]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    read_files: RefCell<BTreeSet<PathBuf>>,
+
     /// This string MUST be the [semantic version number](https://semver.org/spec/v2.0.0.html)
     /// of the
     /// [OpenAPI Specification version](https://github.com/OAI/OpenAPI-Specification/blob/HEAD/versions/3.1.0.md#versions)
@@ -108,13 +120,14 @@ pub struct Spec {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Components>,
 
-    // FIXME: Implement
-    // /// A declaration of which security mechanisms can be used across the API.
-    // /// The list of  values includes alternative security requirement objects that can be used.
-    // /// Only one of the security requirement objects need to be satisfied to authorize a request.
-    // /// Individual operations can override this definition.
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub security: Option<SecurityRequirement>,
+    /// A declaration of which security mechanisms can be used across the API.
+    /// The list of  values includes alternative security requirement objects that can be used.
+    /// Only one of the security requirement objects need to be satisfied to authorize a request.
+    /// Individual operations can override this definition.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub security: Vec<SecurityRequirement>,
+
     /// A list of tags used by the specification with additional metadata.
     ///The order of the tags can be used to reflect on their order by the parsing tools.
     /// Not all tags that are used by the
@@ -146,6 +159,12 @@ pub struct Spec {
 // TODO: Add "Specification Extensions" https://github.com/OAI/OpenAPI-Specification/blob/HEAD/versions/3.1.0.md#specificationExtensions}
 
 impl Spec {
+    /// Files of the root directory, which were read to resolve references so far. Code,
+    /// generated from the spec, depends on them.
+    pub fn read_files(&self) -> Vec<PathBuf> {
+        self.read_files.borrow().iter().cloned().collect()
+    }
+
     pub fn validate_version(&self) -> Result<semver::Version, Error> {
         let spec_version = &self.openapi;
         let sem_ver = semver::Version::parse(spec_version)?;
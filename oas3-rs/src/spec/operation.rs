@@ -4,7 +4,8 @@ use log::error;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    Callback, Error, ExternalDoc, ObjectOrReference, Parameter, RequestBody, Response, Server, Spec,
+    Callback, Error, ExternalDoc, ObjectOrReference, Parameter, RequestBody, Response,
+    SecurityRequirement, Server, Spec,
 };
 
 /// Describes a single API operation on a path.
@@ -92,14 +93,15 @@ pub struct Operation {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deprecated: Option<bool>,
 
-    // FIXME: Implement
-    // /// A declaration of which security mechanisms can be used for this operation. The list of
-    // /// values includes alternative security requirement objects that can be used. Only one
-    // /// of the security requirement objects need to be satisfied to authorize a request.
-    // /// This definition overrides any declared top-level
-    // /// [`security`](https://github.com/OAI/OpenAPI-Specification/blob/HEAD/versions/3.1.0.md#oasSecurity).
-    // /// To remove a top-level security declaration, an empty array can be used.
-    // pub security: Option<SecurityRequirement>,
+    /// A declaration of which security mechanisms can be used for this operation. The list of
+    /// values includes alternative security requirement objects that can be used. Only one
+    /// of the security requirement objects need to be satisfied to authorize a request.
+    /// This definition overrides any declared top-level
+    /// [`security`](https://github.com/OAI/OpenAPI-Specification/blob/HEAD/versions/3.1.0.md#oasSecurity).
+    /// To remove a top-level security declaration, an empty array can be used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<SecurityRequirement>>,
+
     /// An alternative `server` array to service this operation. If an alternative `server`
     /// object is specified at the Path Item Object or Root level, it will be overridden by
     /// this value.
@@ -109,11 +111,17 @@ pub struct Operation {
 }
 
 impl Operation {
+    /// Security requirements in effect for this operation: its own ones or, if it doesn't
+    /// declare any, top-level ones of the spec.
+    pub fn security<'a>(&'a self, spec: &'a Spec) -> &'a [SecurityRequirement] {
+        self.security.as_deref().unwrap_or(&spec.security)
+    }
+
     pub fn request_body(&self, spec: &Spec) -> Result<RequestBody, Error> {
         self.request_body
             .as_ref()
             .unwrap()
-            .resolve(&spec)
+            .resolve(spec)
             .map_err(Error::Ref)
     }
 
@@ -121,7 +129,7 @@ impl Operation {
         self.responses
             .iter()
             .filter_map(|(name, oor)| {
-                oor.resolve(&spec)
+                oor.resolve(spec)
                     .map(|obj| (name.clone(), obj))
                     // TODO: find better error solution
                     .map_err(|err| error!("{}", err))
@@ -135,7 +143,7 @@ impl Operation {
             .parameters
             .iter()
             // TODO: find better error solution, maybe vec<result<_>>
-            .filter_map(|oor| oor.resolve(&spec).map_err(|err| error!("{}", err)).ok())
+            .filter_map(|oor| oor.resolve(spec).map_err(|err| error!("{}", err)).ok())
             .collect();
 
         Ok(params)
@@ -143,7 +151,7 @@ impl Operation {
 
     pub fn parameter(&self, search: &str, spec: &Spec) -> Result<Option<Parameter>, Error> {
         let param = self
-            .parameters(&spec)?
+            .parameters(spec)?
             .iter()
             .find(|param| param.name == search)
             .cloned();
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::fs::File;
 
 use serde::{ de::DeserializeOwned};
@@ -18,16 +18,23 @@ where
         .as_ref()
         .map(|root| {
             let p: &Path = root.as_ref();
-            p.join(path)
+            p.join(&path)
         })
         .expect("WFT");
 
     let schema = File::open(&file_path)
         .map_err(|e| RefError::UnableToReadFromFile(file_path.display().to_string(), e.to_string()))
         .and_then(|reader| {
-            serde_yaml::from_reader::<_, T>(reader).map_err(|e| {
-                RefError::UnableToReadFromFile(file_path.display().to_string(), e.to_string())
-            })
+            spec.read_files.borrow_mut().insert(file_path.clone());
+            serde_yaml::from_reader::<_, serde_yaml::Value>(reader)
+                .and_then(|mut value| {
+                    let base = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
+                    rebase_refs(&mut value, base);
+                    serde_yaml::from_value::<T>(value)
+                })
+                .map_err(|e| {
+                    RefError::UnableToReadFromFile(file_path.display().to_string(), e.to_string())
+                })
         });
     match schema {
         Ok(schema) => {
@@ -40,3 +47,84 @@ where
         )),
     }
 }
+
+/// Relative `$ref`s in a file point from the directory of that file, while spec resolves file
+/// references from its root directory. Rewrites them relative to the root directory.
+fn rebase_refs(value: &mut serde_yaml::Value, base: &Path) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                match (key.as_str(), value) {
+                    (Some("$ref"), serde_yaml::Value::String(reference))
+                        if is_relative_file(reference) =>
+                    {
+                        *reference = normalize(&base.join(&reference)).display().to_string();
+                    }
+                    (_, value) => rebase_refs(value, base),
+                }
+            }
+        }
+        serde_yaml::Value::Sequence(sequence) => {
+            sequence.iter_mut().for_each(|value| rebase_refs(value, base))
+        }
+        _ => {}
+    }
+}
+
+fn is_relative_file(reference: &str) -> bool {
+    !reference.starts_with('#') && !reference.contains("://") && !Path::new(reference).is_absolute()
+}
+
+/// Removes `.` and resolves `..` components, so the same file gets the same path from any file
+/// it is referenced from.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebases_relative_refs_onto_file_directory() {
+        let mut value: serde_yaml::Value = serde_yaml::from_str(
+            r##"
+            properties:
+              nutrients:
+                type: array
+                items:
+                  $ref: ./nutrient.yaml
+              link:
+                $ref: ../common/link.yaml
+              local:
+                $ref: "#/components/schemas/Local"
+            "##,
+        )
+        .unwrap();
+
+        rebase_refs(&mut value, Path::new("./types"));
+
+        let refs = ["nutrients", "link", "local"]
+            .iter()
+            .map(|name| {
+                let property = &value["properties"][*name];
+                let reference = property.get("items").unwrap_or(property)["$ref"].clone();
+                reference.as_str().unwrap().to_owned()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            refs,
+            vec!["types/nutrient.yaml", "common/link.yaml", "#/components/schemas/Local"]
+        );
+    }
+}
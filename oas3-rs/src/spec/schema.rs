@@ -1,12 +1,11 @@
 //! Schema specification for [OpenAPI 3.0.1](https://github.com/OAI/OpenAPI-Specification/blob/HEAD/versions/3.1.0.md)
 
 use std::collections::BTreeMap;
-use std::{fs::File, path::Path};
 
 use derive_more::{Display, Error};
 use serde::{Deserialize, Serialize};
 
-use crate::spec::{schema, FromRef, ObjectOrReference, Ref, RefError, RefType, Spec};
+use crate::spec::{Discriminator, FromRef, ObjectOrReference, Ref, RefError, RefType, Spec};
 
 use super::read_from_file;
 
@@ -183,6 +182,10 @@ pub struct Schema {
     #[serde(rename = "anyOf")]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub any_of: Vec<ObjectOrReference<Schema>>,
+
+    /// Adds support for polymorphism, tells which of `oneOf` or `anyOf` schemas the payload has.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<Discriminator>,
 }
 
 impl FromRef for Schema {
@@ -197,7 +200,7 @@ impl FromRef for Schema {
                     .as_ref()
                     .and_then(|cs| cs.schemas.get(&name))
                     .ok_or_else(|| RefError::Unresolvable(path.to_owned()))
-                    .and_then(|oor| oor.resolve(&spec)),
+                    .and_then(|oor| oor.resolve(spec)),
 
                 typ => Err(RefError::MismatchedType(typ, RefType::Schema)),
             },
@@ -1,6 +1,15 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-use super::Flows;
+use super::{read_from_file, Flows, FromRef, Ref, RefError, RefType, Spec};
+
+/// Lists the required security schemes to execute an operation. Each name MUST correspond to
+/// a security scheme which is declared in the Security Schemes under the Components Object,
+/// values are scopes required for the execution (empty for schemes without scopes).
+///
+/// See <https://github.com/OAI/OpenAPI-Specification/blob/HEAD/versions/3.1.0.md#securityRequirementObject>.
+pub type SecurityRequirement = BTreeMap<String, Vec<String>>;
 
 /// Defines a security scheme that can be used by the operations. Supported schemes are HTTP
 /// authentication, an API key (either as a header or as a query parameter), OAuth2's common flows
@@ -11,7 +20,6 @@ use super::Flows;
 ///
 /// [RFC6749]: https://tools.ietf.org/html/rfc6749
 /// [OpenID Connect Discovery]: https://tools.ietf.org/html/draft-ietf-oauth-discovery-06
-
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type")]
@@ -27,7 +35,8 @@ pub enum SecurityScheme {
     Http {
         scheme: String,
         #[serde(rename = "bearerFormat")]
-        bearer_format: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bearer_format: Option<String>,
     },
 
     #[serde(rename = "oauth2")]
@@ -40,6 +49,26 @@ pub enum SecurityScheme {
     },
 }
 
+impl FromRef for SecurityScheme {
+    fn from_ref(spec: &Spec, path: &str) -> Result<Self, RefError> {
+        let refpath = path.parse::<Ref>()?;
+
+        match refpath {
+            Ref::Path(path) => read_from_file(spec, path),
+            Ref::InFile { kind, name, .. } => match kind {
+                RefType::SecurityScheme => spec
+                    .components
+                    .as_ref()
+                    .and_then(|cs| cs.security_schemes.get(&name))
+                    .ok_or_else(|| RefError::Unresolvable(path.to_owned()))
+                    .and_then(|oor| oor.resolve(spec)),
+
+                typ => Err(RefError::MismatchedType(typ, RefType::SecurityScheme)),
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,7 +97,7 @@ mod tests {
           }
         }"#;
 
-        let obj: SecurityScheme = serde_json::from_str(&IMPLICIT_OAUTH2_SAMPLE).unwrap();
+        let obj: SecurityScheme = serde_json::from_str(IMPLICIT_OAUTH2_SAMPLE).unwrap();
         match obj {
             SecurityScheme::OAuth2 { flows } => {
                 assert!(flows.implicit.is_some());
@@ -93,7 +122,20 @@ mod tests {
                 assert!(implicit.scopes.contains_key("write:pets"));
                 assert!(implicit.scopes.contains_key("read:pets"));
             }
-            _ => assert!(false, "wrong security scheme type"),
+            _ => panic!("wrong security scheme type"),
         }
     }
+
+    #[test]
+    fn test_security_scheme_http_bearer_deser() {
+        let obj: SecurityScheme =
+            serde_json::from_str(r#"{ "type": "http", "scheme": "bearer" }"#).unwrap();
+        assert_eq!(
+            obj,
+            SecurityScheme::Http {
+                scheme: "bearer".into(),
+                bearer_format: None,
+            }
+        );
+    }
 }
@@ -0,0 +1,237 @@
+use oas3::spec::{Operation, SecurityScheme};
+
+use crate::{
+  generate_types::NamedSchemas,
+  options::Options,
+  problems::{Location, Problem},
+  utils,
+};
+
+const PARAM_LOCATIONS: &[&str] = &["path", "query", "header", "cookie"];
+/// Media types of request and response bodies, serde serializes
+const BODY_MIMES: &[&str] = &["application/json", "application/yaml"];
+
+/// Problems of spec in `file`, which keep code from being generated. All of them are collected,
+/// so they can be fixed in one go.
+pub fn check(spec: &oas3::Spec, file: &str, options: &Options) -> Vec<Problem> {
+  let root = Location::root(spec, file);
+  let mut problems = Vec::new();
+  let trait_name = options.trait_name(spec);
+  if !utils::is_ident(&trait_name) {
+    let message = format!("Trait name {trait_name:?} is not valid, it can be set explicitly");
+    problems.push(Problem::new(&root.join("info").join("title"), message));
+  }
+  check_security_schemes(spec, &root, &mut problems);
+  for (path, method, op) in spec.operations() {
+    let at = root
+      .join("paths")
+      .join(&path)
+      .join(method.as_str().to_lowercase());
+    if op.operation_id.is_none() {
+      let message = format!("Operation id is not provided for {path} method: {method}");
+      problems.push(Problem::new(&at, message));
+    }
+    check_parameters(spec, op, &at, options, &mut problems);
+    check_request_body(spec, op, &at, &mut problems);
+    check_responses(spec, op, &at, &mut problems);
+    check_security(spec, op, &at, &mut problems);
+  }
+  problems.extend(NamedSchemas::collect(spec, &root, options).problems);
+  problems
+}
+
+fn check_security_schemes(spec: &oas3::Spec, root: &Location, problems: &mut Vec<Problem>) {
+  let schemes = spec
+    .components
+    .iter()
+    .flat_map(|components| components.security_schemes.iter());
+  for (name, scheme) in schemes {
+    let at = root.join("components").join("securitySchemes").join(name);
+    match scheme.resolve(spec) {
+      Ok(SecurityScheme::Http { .. }) => {}
+      Ok(SecurityScheme::ApiKey { location, .. }) => {
+        if !["header", "query", "cookie"].contains(&location.as_str()) {
+          let message = format!("Api key in {location} is not supported");
+          problems.push(Problem::new(&at.join("in"), message));
+        }
+      }
+      Ok(_) => {
+        let message = format!("Security scheme {name} is not supported, only http and apiKey are");
+        problems.push(Problem::new(&at, message));
+      }
+      Err(err) => {
+        let message = format!("Security scheme {name} is not resolved: {err}");
+        problems.push(Problem::new(&at, message));
+      }
+    }
+  }
+}
+
+fn check_parameters(
+  spec: &oas3::Spec,
+  op: &Operation,
+  at: &Location,
+  options: &Options,
+  problems: &mut Vec<Problem>,
+) {
+  for (index, param) in op.parameters.iter().enumerate() {
+    let at = at.join("parameters").join(index);
+    let param = match param.resolve(spec) {
+      Ok(param) => param,
+      Err(err) => {
+        problems.push(Problem::new(
+          &at,
+          format!("Parameter is not resolved: {err}"),
+        ));
+        continue;
+      }
+    };
+    if !PARAM_LOCATIONS.contains(&param.location.as_str()) {
+      let message = format!("Parameters in {} are not supported", param.location);
+      problems.push(Problem::new(&at.join("in"), message));
+    }
+    let schema = match param.schema.as_ref().map(|schema| schema.resolve(spec)) {
+      Some(Ok(schema)) => schema,
+      Some(Err(err)) => {
+        let message = format!("Schema of parameter {} is not resolved: {err}", param.name);
+        problems.push(Problem::new(&at.join("schema"), message));
+        continue;
+      }
+      None => {
+        let message = format!("Parameter {} has no schema", param.name);
+        problems.push(Problem::new(&at, message));
+        continue;
+      }
+    };
+    if schema.title.is_none() && utils::primitive_type(&schema, options).is_none() {
+      let message = format!("Parameter {} needs schema with title", param.name);
+      problems.push(Problem::new(&at.join("schema"), message));
+    }
+  }
+}
+
+fn check_request_body(
+  spec: &oas3::Spec,
+  op: &Operation,
+  at: &Location,
+  problems: &mut Vec<Problem>,
+) {
+  let at = at.join("requestBody");
+  let request_body = match op.request_body.as_ref().map(|rb| rb.resolve(spec)) {
+    Some(Ok(request_body)) => request_body,
+    Some(Err(err)) => {
+      problems.push(Problem::new(
+        &at,
+        format!("Request body is not resolved: {err}"),
+      ));
+      return;
+    }
+    None => return,
+  };
+  let mut body_type: Option<String> = None;
+  for (mime, media) in request_body.content.iter() {
+    let at = at.join("content").join(mime);
+    if !BODY_MIMES.contains(&mime.as_str()) {
+      let message = format!("Request body of {mime} is not supported");
+      problems.push(Problem::new(&at, message));
+    }
+    let title = media
+      .schema
+      .as_ref()
+      .and_then(|schema| schema.resolve(spec).ok())
+      .and_then(|schema| schema.title);
+    match (title, &body_type) {
+      (None, _) => {
+        let message = format!("Request body of {mime} needs schema with title");
+        problems.push(Problem::new(&at.join("schema"), message));
+      }
+      (Some(title), Some(body_type)) if title != *body_type => {
+        let message = format!("Request body has different schemas {body_type} and {title}");
+        problems.push(Problem::new(&at.join("schema"), message));
+      }
+      (Some(title), _) => body_type = Some(title),
+    }
+  }
+}
+
+fn is_response_code(code: &str) -> bool {
+  let digits = code.chars().filter(|c| c.is_ascii_digit()).count();
+  code == "default"
+    || code.len() == 3 && digits == 3
+    || code.len() == 3 && digits == 1 && code[1..].eq_ignore_ascii_case("XX")
+}
+
+fn check_responses(spec: &oas3::Spec, op: &Operation, at: &Location, problems: &mut Vec<Problem>) {
+  for (code, response) in op.responses.iter() {
+    let at = at.join("responses").join(code);
+    if !is_response_code(code) {
+      let message = format!("Response code {code} is not supported");
+      problems.push(Problem::new(&at, message));
+    }
+    let response = match response.resolve(spec) {
+      Ok(response) => response,
+      Err(err) => {
+        problems.push(Problem::new(
+          &at,
+          format!("Response is not resolved: {err}"),
+        ));
+        continue;
+      }
+    };
+    let mut response_schema: Option<oas3::Schema> = None;
+    for (mime, media) in response.content.iter() {
+      let at = at.join("content").join(mime);
+      if !BODY_MIMES.contains(&mime.as_str()) {
+        let message = format!("Response body of {mime} is not supported");
+        problems.push(Problem::new(&at, message));
+      }
+      // Unresolved schemas and the ones without Rust type are reported, when named schemas are
+      // collected.
+      let schema = match media.schema.as_ref().map(|schema| schema.resolve(spec)) {
+        Some(Ok(schema)) => schema,
+        Some(Err(_)) => continue,
+        None => {
+          problems.push(Problem::new(&at, "Response body needs schema"));
+          continue;
+        }
+      };
+      match &response_schema {
+        Some(known) if *known != schema => {
+          let message = format!("Response {code} has different schemas for media types");
+          problems.push(Problem::new(&at.join("schema"), message));
+        }
+        _ => response_schema = Some(schema),
+      }
+    }
+  }
+}
+
+fn check_security(spec: &oas3::Spec, op: &Operation, at: &Location, problems: &mut Vec<Problem>) {
+  // Operation without own requirements inherits the ones of the whole api
+  let at = match op.security {
+    Some(_) => at.join("security"),
+    None => at.reference("#/security"),
+  };
+  let schemes = spec
+    .components
+    .iter()
+    .flat_map(|components| components.security_schemes.keys())
+    .collect::<Vec<_>>();
+  for (index, requirement) in op.security(spec).iter().enumerate() {
+    let at = at.join(index);
+    if requirement.is_empty() {
+      let message =
+        "Empty security requirement, which makes authentication optional, is not supported";
+      problems.push(Problem::new(&at, message));
+    } else if requirement.len() > 1 {
+      let message = "Only requirements with single security scheme are supported";
+      problems.push(Problem::new(&at, message));
+    }
+    for name in requirement.keys() {
+      if !schemes.contains(&name) {
+        let message = format!("Security scheme {name} is not declared in components");
+        problems.push(Problem::new(&at.join(name), message));
+      }
+    }
+  }
+}
@@ -0,0 +1,429 @@
+use std::collections::BTreeMap;
+
+use convert_case::{Case, Casing};
+use oas3::spec::{Operation, Parameter, SecurityScheme};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+
+use crate::{generate_trait, options::Options, security, utils};
+
+/// Where operation puts its parameters and credentials, so only the needed collections are
+/// generated.
+#[derive(Default)]
+struct Locations {
+  query: bool,
+  header: bool,
+  cookie: bool,
+}
+
+impl Locations {
+  fn add(&mut self, location: &str) {
+    match location {
+      "query" => self.query = true,
+      "header" => self.header = true,
+      "cookie" => self.cookie = true,
+      _ => {}
+    }
+  }
+}
+
+/// `format!` arguments of operation path, path parameters are percent encoded
+fn path_format(path: &str) -> (String, Vec<TokenStream>) {
+  let mut args = Vec::new();
+  let format = path
+    .split('/')
+    .map(|segment| {
+      match segment
+        .strip_prefix('{')
+        .and_then(|segment| segment.strip_suffix('}'))
+      {
+        Some(param) => {
+          let var = utils::snake_ident(param);
+          args.push(quote!(encode(&#var.to_string())));
+          "{}".to_owned()
+        }
+        None => segment.to_owned(),
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("/");
+  (format, args)
+}
+
+/// Statement, which puts parameter value into query, headers or cookies
+fn put_param(param: &Parameter, spec: &oas3::Spec) -> Option<TokenStream> {
+  let name = &param.name;
+  let var = utils::snake_ident(name);
+  let schema = utils::param_schema(param, spec);
+  let put = match param.location.as_str() {
+    "path" => return None,
+    // Object parameter is spread over the whole query string, as router expects it.
+    "query" if !utils::is_scalar(&schema) => quote!(query.push(serde_qs::to_string(value)?)),
+    "query" => quote!(query.push(format!("{}={}", encode(#name), encode(&value.to_string())))),
+    // Object in header is in `simple` style, keys and values separated by commas, as router
+    // expects it.
+    "header" if !utils::is_scalar(&schema) => {
+      quote!(headers.push((#name, serde_qs::to_string(value)?.replace(['&', '='], ","))))
+    }
+    "header" => quote!(headers.push((#name, value.to_string()))),
+    "cookie" => quote!(cookies.push(format!("{}={}", #name, encode(&value.to_string())))),
+    _ => unreachable!("Parameter locations are checked"),
+  };
+  if utils::is_required(param) {
+    Some(quote! {
+      let value = &#var;
+      #put;
+    })
+  } else {
+    Some(quote! {
+      if let Some(value) = &#var {
+        #put;
+      }
+    })
+  }
+}
+
+/// Statement, which puts credentials of the client into request for one of operation schemes
+fn put_credentials(
+  op: &Operation,
+  spec: &oas3::Spec,
+  locations: &mut Locations,
+) -> Option<TokenStream> {
+  let names = security::operation_schemes(op, spec);
+  if names.is_empty() {
+    return None;
+  }
+  let schemes = security::security_schemes(spec);
+  let arms = names.iter().map(|name| {
+    let (_, scheme) = schemes
+      .iter()
+      .find(|(scheme_name, _)| scheme_name == name)
+      .expect("Security schemes of operations are checked");
+    let variant = Ident::new(&name.to_case(Case::Pascal), Span::call_site());
+    let put = match scheme {
+      SecurityScheme::Http { scheme, .. } => {
+        locations.add("header");
+        let prefix = scheme.to_case(Case::Pascal);
+        quote!(headers.push(("authorization", format!("{} {}", #prefix, value))))
+      }
+      SecurityScheme::ApiKey { name, location } => {
+        locations.add(location);
+        match location.as_str() {
+          "header" => quote!(headers.push((#name, value.to_owned()))),
+          "query" => quote!(query.push(format!("{}={}", encode(#name), encode(value)))),
+          "cookie" => quote!(cookies.push(format!("{}={}", #name, encode(value)))),
+          _ => unreachable!("Api key locations are checked"),
+        }
+      }
+      _ => unreachable!("Security schemes are checked"),
+    };
+    quote!(Some(Credentials::#variant(value)) => #put,)
+  });
+  let missing = format!("Operation needs credentials of {}", names.join(" or "));
+  Some(quote! {
+    match &self.credentials {
+      #(#arms)*
+      _ => return Err(#missing.into()),
+    }
+  })
+}
+
+/// Statement, which finishes request builder with body serialized into the first media type
+fn request_body(op: &Operation, spec: &oas3::Spec) -> TokenStream {
+  match utils::request_body_type(op, spec) {
+    Some((title, mimes)) => {
+      let var = utils::snake_ident(&title);
+      let mime = mimes.first().cloned().unwrap_or_default();
+      let serialized = match mime.as_str() {
+        "application/yaml" => quote!(serde_yaml::to_string(&#var)?),
+        "application/json" => quote!(serde_json::to_vec(&#var)?),
+        _ => unreachable!("Request body media types are checked"),
+      };
+      quote! {
+        request
+          .header(hyper::header::CONTENT_TYPE, #mime)
+          .body(hyper::Body::from(#serialized))?
+      }
+    }
+    None => quote!(request.body(hyper::Body::empty())?),
+  }
+}
+
+/// Pattern of status codes, which response code stands for
+fn status_pattern(code: &str) -> TokenStream {
+  match code {
+    "default" => quote!(_),
+    code if code.ends_with("XX") || code.ends_with("xx") => {
+      let class = code[..1]
+        .parse::<u16>()
+        .expect("Response codes are checked");
+      let (start, end) = (
+        Literal::u16_unsuffixed(class * 100),
+        Literal::u16_unsuffixed(class * 100 + 99),
+      );
+      quote!(#start..=#end)
+    }
+    code => {
+      let status = code.parse::<u16>().expect("Response codes are checked");
+      let status = Literal::u16_unsuffixed(status);
+      quote!(#status)
+    }
+  }
+}
+
+/// Match of response status into operation result, the way trait of the api returns it
+fn decode_response(
+  method_name: &str,
+  responses: &BTreeMap<String, BTreeMap<String, String>>,
+) -> TokenStream {
+  let mut arms = Vec::new();
+  if responses.len() > 1 {
+    let result_type = generate_trait::result_type_name(method_name);
+    for (code, media_types) in responses {
+      let pattern = status_pattern(code);
+      let variant = Ident::new(&generate_trait::code_to_name(code), Span::call_site());
+      if media_types.is_empty() {
+        arms.push(quote!(#pattern => Ok(#result_type::#variant),));
+      } else {
+        arms.push(quote!(#pattern => Ok(#result_type::#variant(decode(&content_type, &body)?)),));
+      }
+    }
+  } else if let Some((code, media_types)) = responses.iter().next() {
+    let pattern = status_pattern(code);
+    if media_types.is_empty() {
+      arms.push(quote!(#pattern => Ok(()),));
+    } else {
+      arms.push(quote!(#pattern => Ok(decode(&content_type, &body)?),));
+    }
+  } else {
+    arms.push(quote!(200..=299 => Ok(()),));
+  }
+  if !responses.contains_key("default") {
+    arms.push(quote!(status => Err(unexpected(status, &body)),));
+  }
+  quote! {
+    match status {
+      #(#arms)*
+    }
+  }
+}
+
+fn create_client_method(
+  path: &str,
+  method: &http::Method,
+  op: &Operation,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> TokenStream {
+  let method_name = op.operation_id.as_ref().expect("Operation ids are checked");
+  let method_name_ident = utils::snake_ident(method_name);
+  let mut params = generate_trait::collect_params(spec, op, options);
+  params.extend(generate_trait::collect_body_params(spec, op, options));
+  let responses = generate_trait::collect_response_types(spec, op, options);
+  let (result, _) = generate_trait::build_result_type(method_name, responses.clone());
+
+  let params_objects = op
+    .parameters
+    .iter()
+    .flat_map(|param| param.resolve(spec))
+    .collect::<Vec<_>>();
+  let mut locations = Locations::default();
+  for param in params_objects.iter() {
+    locations.add(&param.location);
+  }
+  let put_params = params_objects
+    .iter()
+    .filter_map(|param| put_param(param, spec))
+    .collect::<Vec<_>>();
+  let put_credentials = put_credentials(op, spec, &mut locations);
+
+  let query = locations
+    .query
+    .then(|| quote!(let mut query: Vec<String> = Vec::new();));
+  let add_query = locations.query.then(|| {
+    quote! {
+      let uri = if query.is_empty() {
+        uri
+      } else {
+        format!("{uri}?{}", query.join("&"))
+      };
+    }
+  });
+  let headers = locations
+    .header
+    .then(|| quote!(let mut headers: Vec<(&'static str, String)> = Vec::new();));
+  let add_headers = locations.header.then(|| {
+    quote! {
+      let request = headers
+        .into_iter()
+        .fold(request, |request, (name, value)| request.header(name, value));
+    }
+  });
+  let cookies = locations
+    .cookie
+    .then(|| quote!(let mut cookies: Vec<String> = Vec::new();));
+  let add_cookies = locations.cookie.then(|| {
+    quote! {
+      let request = if cookies.is_empty() {
+        request
+      } else {
+        request.header(hyper::header::COOKIE, cookies.join("; "))
+      };
+    }
+  });
+
+  let (path_format, path_args) = path_format(path);
+  let http_method = Ident::new(method.as_str(), Span::call_site());
+  let accept = responses
+    .values()
+    .flat_map(|media_types| media_types.keys())
+    .cloned()
+    .collect::<std::collections::BTreeSet<_>>()
+    .into_iter()
+    .collect::<Vec<_>>()
+    .join(", ");
+  let accept = (!accept.is_empty()).then(|| quote!(.header(hyper::header::ACCEPT, #accept)));
+  let body = request_body(op, spec);
+  let decode = decode_response(method_name, &responses);
+  // Content type is needed only to decode bodies, body is also shown for unexpected statuses.
+  let decodes = responses
+    .values()
+    .any(|media_types| !media_types.is_empty());
+  let content_type = if decodes {
+    quote!(content_type)
+  } else {
+    quote!(_)
+  };
+  let body_var = if decodes || !responses.contains_key("default") {
+    quote!(body)
+  } else {
+    quote!(_)
+  };
+
+  quote! {
+    pub async fn #method_name_ident(&self, #(#params),*) -> Result<#result, Error> {
+      #query
+      #headers
+      #cookies
+      #(#put_params)*
+      #put_credentials
+      let uri = format!(concat!("{}", #path_format), self.base_url.trim_end_matches('/') #(, #path_args)*);
+      #add_query
+      let request = hyper::Request::builder()
+        .method(hyper::Method::#http_method)
+        .uri(uri)
+        #accept;
+      #add_headers
+      #add_cookies
+      let request = #body;
+      let (status, #content_type, #body_var) = self.send(request).await?;
+      #decode
+    }
+  }
+}
+
+pub fn run(spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let title = &spec.info.title;
+  let methods = spec
+    .operations()
+    .map(|(path, method, op)| create_client_method(&path, &method, op, spec, options))
+    .collect::<Vec<_>>();
+  let has_credentials = !security::security_schemes(spec).is_empty();
+  let credentials_field = has_credentials.then(|| quote!(credentials: Option<Credentials>,));
+  let credentials_init = has_credentials.then(|| quote!(credentials: None,));
+  let with_credentials = has_credentials.then(|| {
+    quote! {
+      /// Client, which authenticates secured operations with `credentials`
+      pub fn with_credentials(self, credentials: Credentials) -> Self {
+        Client {
+          credentials: Some(credentials),
+          ..self
+        }
+      }
+    }
+  });
+  let client_doc = format!("Client of {title} api");
+
+  quote! {
+    use super::traits::*;
+    use super::types::*;
+
+    #[doc = #client_doc]
+    #[derive(Clone)]
+    pub struct Client<C = hyper::client::HttpConnector> {
+      base_url: String,
+      http: hyper::Client<C>,
+      #credentials_field
+    }
+
+    impl Client {
+      /// Client of api at `base_url`, like `http://localhost:8080`
+      pub fn new(base_url: impl Into<String>) -> Self {
+        Client::with_http_client(base_url, hyper::Client::new())
+      }
+    }
+
+    /// Percent encoded path segment, query component or cookie value
+    #[allow(dead_code)]
+    fn encode(value: &str) -> String {
+      value
+        .bytes()
+        .map(|byte| match byte {
+          b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+            (byte as char).to_string()
+          }
+          byte => format!("%{byte:02X}"),
+        })
+        .collect()
+    }
+
+    /// Response body parsed according to its content type
+    #[allow(dead_code)]
+    fn decode<T: serde::de::DeserializeOwned>(content_type: &str, body: &[u8]) -> Result<T, Error> {
+      if content_type.starts_with("application/yaml") {
+        Ok(serde_yaml::from_slice(body)?)
+      } else {
+        Ok(serde_json::from_slice(body)?)
+      }
+    }
+
+    fn unexpected(status: u16, body: &[u8]) -> Error {
+      format!("Unexpected response {status}: {}", String::from_utf8_lossy(body)).into()
+    }
+
+    impl<C> Client<C>
+    where
+      C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+    {
+      /// Client of api at `base_url`, which sends requests with `http`, for instance one with
+      /// TLS connector
+      pub fn with_http_client(base_url: impl Into<String>, http: hyper::Client<C>) -> Self {
+        Client {
+          base_url: base_url.into(),
+          http,
+          #credentials_init
+        }
+      }
+
+      #with_credentials
+
+      async fn send(
+        &self,
+        request: hyper::Request<hyper::Body>,
+      ) -> Result<(u16, String, hyper::body::Bytes), Error> {
+        let response = self.http.request(request).await?;
+        let status = response.status().as_u16();
+        let content_type = response
+          .headers()
+          .get(hyper::header::CONTENT_TYPE)
+          .and_then(|value| value.to_str().ok())
+          .unwrap_or_default()
+          .to_owned();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok((status, content_type, body))
+      }
+
+      #(#methods)*
+    }
+  }
+}
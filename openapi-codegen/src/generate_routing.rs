@@ -0,0 +1,616 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use convert_case::{Case, Casing};
+use oas3::spec::{Operation, Parameter, SecurityScheme};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+
+use crate::{generate_trait, options::Options, security, utils};
+
+fn function_name(op: &Operation) -> Ident {
+  let title = op
+    .operation_id
+    .as_ref()
+    .expect("Operation ids are checked")
+    .to_case(Case::Snake);
+  Ident::new(&title, Span::call_site())
+}
+
+fn create_routing_instruction(
+  path: &str,
+  method: &http::method::Method,
+  op: &Operation,
+) -> TokenStream {
+  let title = function_name(op);
+  let method = Ident::new(&method.as_str().to_case(Case::Lower), Span::call_site());
+  let path = routerify_path(path);
+
+  quote! {
+    .#method(#path, #title::<Api>)
+  }
+}
+
+/// Routerify denotes path params as `:param` instead of `{param}` of openapi
+fn routerify_path(path: &str) -> String {
+  path
+    .split('/')
+    .map(|segment| {
+      segment
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .map(|param| format!(":{param}"))
+        .unwrap_or_else(|| segment.to_owned())
+    })
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Expression, which converts raw `value` of parameter into its type. Scalars are parsed. Objects
+/// in headers are in `simple` style, keys and values separated by commas, like `k1,v1,k2,v2`;
+/// they are read as a form of the same keys and values. Other objects are JSON.
+fn convert_value(param: &Parameter, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let schema = utils::param_schema(param, spec);
+  let value_type = utils::param_value_type(param, spec, options);
+  if utils::is_scalar(&schema) {
+    quote!(value.parse::<#value_type>().map_err(|err| err.to_string()))
+  } else if param.location == "header" {
+    quote! {{
+      let form = value
+        .split(',')
+        .collect::<Vec<_>>()
+        .chunks(2)
+        .map(|pair| pair.join("="))
+        .collect::<Vec<_>>()
+        .join("&");
+      serde_qs::from_str::<#value_type>(&form).map_err(|err| err.to_string())
+    }}
+  } else {
+    quote!(serde_json::from_str::<#value_type>(&value).map_err(|err| err.to_string()))
+  }
+}
+
+/// Statement, which converts raw `Option<String>` value of parameter into its type. Variable
+/// becomes `Some` with the value as operation receives it, or `None` if there is a problem with
+/// it, which is recorded into `problems`.
+fn typed_param(
+  param: &Parameter,
+  spec: &oas3::Spec,
+  raw_value: TokenStream,
+  options: &Options,
+) -> TokenStream {
+  let name = &param.name;
+  let location = &param.location;
+  let name_snake_id = utils::snake_ident(name);
+  let convert = convert_value(param, spec, options);
+  let missing = if utils::is_required(param) {
+    quote! {
+      None => {
+        problems.push((#name, #location, "is required".to_owned()));
+        None
+      }
+    }
+  } else {
+    quote!(None => Some(None),)
+  };
+  let found = if utils::is_required(param) {
+    quote!(Some(value))
+  } else {
+    quote!(Some(Some(value)))
+  };
+  quote! {
+    let #name_snake_id = match (#raw_value).map(|value: String| #convert) {
+      Some(Ok(value)) => #found,
+      Some(Err(reason)) => {
+        problems.push((#name, #location, reason));
+        None
+      }
+      #missing
+    };
+  }
+}
+
+fn var_from_path(param: &Parameter, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let name = &param.name;
+  typed_param(param, spec, quote!(req.param(#name).cloned()), options)
+}
+
+fn var_from_query(param: &Parameter, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let name = &param.name;
+  let schema = utils::param_schema(param, spec);
+
+  if utils::is_scalar(&schema) {
+    // Query, which can't be parsed, is already a problem, so its parameters are not reported.
+    let name_snake_id = utils::snake_ident(name);
+    let extraction = typed_param(param, spec, quote!(query.get(#name).cloned()), options);
+    quote! {
+      let #name_snake_id = match &query_pairs {
+        Some(query) => {
+          #extraction
+          #name_snake_id
+        }
+        None => None,
+      };
+    }
+  } else {
+    // Object parameter is spread over the whole query string, as `form` style with `explode`.
+    let name_snake_id = utils::snake_ident(name);
+    let location = &param.location;
+    let type_name_id = utils::param_value_type(param, spec, options);
+    let found = if utils::is_required(param) {
+      quote!(Some(value))
+    } else {
+      quote!(Some(Some(value)))
+    };
+    let missing = if utils::is_required(param) {
+      quote! {
+        problems.push((#name, #location, "is required".to_owned()));
+        None
+      }
+    } else {
+      quote!(Some(None))
+    };
+    quote! {
+      let #name_snake_id = match req.uri().query().filter(|query| !query.is_empty()) {
+        Some(query) => match serde_qs::from_str::<#type_name_id>(query) {
+          Ok(value) => #found,
+          Err(err) => {
+            problems.push((#name, #location, err.to_string()));
+            None
+          }
+        },
+        None => { #missing }
+      };
+    }
+  }
+}
+
+fn var_from_header(param: &Parameter, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let name = &param.name;
+  let raw_value = quote! {
+    req
+      .headers()
+      .get(#name)
+      .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+  };
+  typed_param(param, spec, raw_value, options)
+}
+
+fn var_from_cookie(param: &Parameter, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let name = &param.name;
+  let raw_value = quote! {
+    req
+      .headers()
+      .get_all(hyper::header::COOKIE)
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .flat_map(|value| value.split(';'))
+      .filter_map(|cookie| cookie.trim().split_once('='))
+      .find(|(cookie, _)| *cookie == #name)
+      .map(|(_, value)| decode_cookie(value))
+  };
+  typed_param(param, spec, raw_value, options)
+}
+
+fn var_from_param(param: &Parameter, spec: &oas3::Spec, options: &Options) -> (TokenStream, Ident) {
+  let extraction = match param.location.as_str() {
+    "query" => var_from_query(param, spec, options),
+    "header" => var_from_header(param, spec, options),
+    "path" => var_from_path(param, spec, options),
+    "cookie" => var_from_cookie(param, spec, options),
+    _ => unreachable!("Parameter locations are checked"),
+  };
+  let name = utils::snake_ident(&param.name);
+  (extraction, name)
+}
+
+/// Statement, which parses query into `query_pairs` once for all scalar query parameters
+fn parse_query(op: &Operation, spec: &oas3::Spec) -> Option<TokenStream> {
+  let has_scalars = op
+    .parameters
+    .iter()
+    .flat_map(|param| param.resolve(spec))
+    .any(|param| param.location == "query" && utils::is_scalar(&utils::param_schema(&param, spec)));
+  has_scalars.then(|| {
+    quote! {
+      let query_pairs = match serde_qs::from_str::<std::collections::HashMap<String, String>>(
+        req.uri().query().unwrap_or_default(),
+      ) {
+        Ok(query) => Some(query),
+        Err(err) => {
+          problems.push(("query", "query", err.to_string()));
+          None
+        }
+      };
+    }
+  })
+}
+
+fn get_vars_from_params(
+  op: &Operation,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> Vec<(TokenStream, Ident)> {
+  op.parameters
+    .iter()
+    .flat_map(|param| {
+      param
+        .resolve(spec)
+        .map(|param| var_from_param(&param, spec, options))
+    })
+    .collect()
+}
+
+fn get_parse_instruction(mime: &str, type_name: &TokenStream) -> TokenStream {
+  match mime {
+    "application/yaml" => {
+      quote!(serde_yaml::from_slice::<#type_name>(&body).map_err(|err| err.to_string()))
+    }
+    "application/json" => {
+      quote!(serde_json::from_slice::<#type_name>(&body).map_err(|err| err.to_string()))
+    }
+    _ => unreachable!("Request body media types are checked"),
+  }
+}
+
+/// Body is parsed according to `Content-Type` of request, the first declared media type is used
+/// if request has none.
+fn get_vars_from_request(
+  op: &Operation,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> Option<(TokenStream, Ident)> {
+  let (type_name, mimes) = utils::request_body_type(op, spec)?;
+  let var_ident = utils::snake_ident(&type_name);
+  let type_ident = options.titled_type(&type_name);
+  let default_mime = mimes.first().cloned().unwrap_or_default();
+  let attempts = mimes.iter().map(|mime| {
+    let parse_instruction = get_parse_instruction(mime, &type_ident);
+    quote!(#mime => #parse_instruction,)
+  });
+
+  Some((
+    quote! {
+      let content_type = req
+        .headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_owned())
+        .unwrap_or_else(|| #default_mime.to_owned());
+      let #var_ident = match hyper::body::to_bytes(req.body_mut()).await {
+        Ok(body) => match content_type.as_str() {
+          #(#attempts)*
+          other => Err(format!("content type {other} is not supported")),
+        },
+        Err(err) => Err(err.to_string()),
+      };
+      let #var_ident = match #var_ident {
+        Ok(value) => Some(value),
+        Err(reason) => {
+          problems.push(("body", "body", reason));
+          None
+        }
+      };
+    },
+    var_ident,
+  ))
+}
+
+/// Statements, which extract all operation input into variables with the names, they are
+/// passed to operation with. All problems with request are collected and answered with 400
+/// at once.
+fn get_operation_input(
+  op: &Operation,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> (TokenStream, Vec<Ident>) {
+  let mut inputs = get_vars_from_params(op, spec, options);
+  inputs.extend(get_vars_from_request(op, spec, options));
+  if inputs.is_empty() {
+    return (quote!(), Vec::new());
+  }
+
+  let (extractions, names): (Vec<_>, Vec<_>) = inputs.into_iter().unzip();
+  let extracted = quote!((#(Some(#names),)*));
+  let parse_query = parse_query(op, spec);
+  let statements = quote! {
+    let mut problems: Vec<(&'static str, &'static str, String)> = Vec::new();
+    #parse_query
+    #(#extractions)*
+    let (#(#names,)*) = match (#(#names,)*) {
+      #extracted => (#(#names,)*),
+      _ => return bad_request(problems),
+    };
+  };
+  (statements, names)
+}
+
+fn status_code(code: &str) -> u16 {
+  match code {
+    "default" => 500,
+    code => code
+      .replace(['X', 'x'], "0")
+      .parse()
+      .expect("Response codes are checked"),
+  }
+}
+
+/// Response with body of the single type for all media types
+fn respond_with(
+  code: &str,
+  media_types: &BTreeMap<String, String>,
+  body: TokenStream,
+) -> TokenStream {
+  let status = Literal::u16_unsuffixed(status_code(code));
+  if media_types.is_empty() {
+    return quote!(empty_response(#status));
+  }
+  let distinct_types = media_types.values().collect::<BTreeSet<_>>();
+  if distinct_types.len() > 1 {
+    unreachable!("Response schemas are checked to be the same for all media types");
+  }
+  let mimes = media_types.keys();
+  quote!(respond(#status, &accept, &[#(#mimes),*], &#body))
+}
+
+/// Turns operation result into response with status code of the result and body serialized into
+/// media type, which request accepts.
+fn use_operation_result(op: &Operation, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let method_name = op.operation_id.as_ref().expect("Operation ids are checked");
+  let responses = generate_trait::collect_response_types(spec, op, options);
+  if responses.len() > 1 {
+    let result_type = generate_trait::result_type_name(method_name);
+    let arms = responses.iter().map(|(code, media_types)| {
+      let variant = Ident::new(&generate_trait::code_to_name(code), Span::call_site());
+      let response = respond_with(code, media_types, quote!(body));
+      if media_types.is_empty() {
+        quote!(super::traits::#result_type::#variant => #response,)
+      } else {
+        quote!(super::traits::#result_type::#variant(body) => #response,)
+      }
+    });
+    quote! {
+      match result {
+        #(#arms)*
+      }
+    }
+  } else if let Some((code, media_types)) = responses.iter().next() {
+    let response = respond_with(code, media_types, quote!(result));
+    if media_types.is_empty() {
+      quote! {
+        let () = result;
+        #response
+      }
+    } else {
+      response
+    }
+  } else {
+    quote! {
+      let () = result;
+      empty_response(200)
+    }
+  }
+}
+
+fn create_routing_function(op: &Operation, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let title = options.trait_ident(spec);
+  let function_name = function_name(op);
+  let (operation_vars, mut operation_var_names) = get_operation_input(op, spec, options);
+  let authenticate = security::authenticate_user(op, spec);
+  if authenticate.is_some() {
+    operation_var_names.insert(0, Ident::new("user", Span::call_site()));
+  }
+  let use_op = use_operation_result(op, spec, options);
+  let has_body = generate_trait::collect_response_types(spec, op, options)
+    .values()
+    .any(|media_types| !media_types.is_empty());
+  let accept = has_body.then(|| {
+    quote! {
+      let accept = req
+        .headers()
+        .get(hyper::header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned());
+    }
+  });
+  quote! {
+    async fn #function_name<Api>(mut req: hyper::Request<hyper::Body>) -> Result<hyper::Response<hyper::Body>>
+      where Api: super::traits::#title
+    {
+      let api = req
+        .data::<std::sync::Arc<Api>>()
+        .cloned()
+        .expect("Api is registered as router data");
+      #accept
+      #authenticate
+      #operation_vars
+
+      let result = api.#function_name(#(#operation_var_names),*).await?;
+
+      #use_op
+    }
+  }
+}
+
+fn create_routing_functions(
+  spec: &oas3::Spec,
+  options: &Options,
+) -> (Vec<TokenStream>, Vec<TokenStream>) {
+  spec
+    .operations()
+    .map(|(path, method, op)| {
+      (
+        create_routing_function(op, spec, options),
+        create_routing_instruction(&path, &method, op),
+      )
+    })
+    .unzip()
+}
+
+/// Some operation reads cookies: parameters or api keys, which clients percent encode
+fn reads_cookies(spec: &oas3::Spec) -> bool {
+  let schemes = security::security_schemes(spec);
+  spec.operations().any(|(_, _, op)| {
+    let cookie_params = op
+      .parameters
+      .iter()
+      .flat_map(|param| param.resolve(spec))
+      .any(|param| param.location == "cookie");
+    let cookie_keys = security::operation_schemes(op, spec).iter().any(|name| {
+      schemes.iter().any(|(scheme_name, scheme)| {
+        scheme_name == name
+          && matches!(scheme, SecurityScheme::ApiKey { location, .. } if location == "cookie")
+      })
+    });
+    cookie_params || cookie_keys
+  })
+}
+
+pub fn run(schema: &oas3::Spec, options: &Options) -> TokenStream {
+  let title = options.trait_ident(schema);
+  let (functions, routing_instructions) = create_routing_functions(schema, options);
+  let decode_cookie = reads_cookies(schema).then(|| {
+    quote! {
+      /// Percent decoded cookie value, bytes, which are not valid UTF-8, are replaced
+      fn decode_cookie(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut index = 0;
+        while index < bytes.len() {
+          let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+          match escaped {
+            Some(byte) => {
+              decoded.push(byte);
+              index += 3;
+            }
+            None => {
+              decoded.push(bytes[index]);
+              index += 1;
+            }
+          }
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+      }
+    }
+  });
+  quote! {
+    pub fn create_routing_table<Api>(api: Api)
+    -> routerify::Router<hyper::Body, Box<dyn std::error::Error + Send + Sync>>
+      where Api: super::traits::#title {
+      type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+      use routerify::{prelude::*, Router};
+      use super::types::*;
+
+      /// Problem details (RFC 7807) response with every invalid parameter of request
+      fn bad_request(
+        problems: Vec<(&'static str, &'static str, String)>,
+      ) -> Result<hyper::Response<hyper::Body>> {
+        let detail = problems
+          .iter()
+          .map(|(name, location, reason)| format!("{location} parameter {name}: {reason}"))
+          .collect::<Vec<_>>()
+          .join("; ");
+        let invalid_params = problems
+          .into_iter()
+          .map(|(name, location, reason)| {
+            serde_json::json!({ "name": name, "in": location, "reason": reason })
+          })
+          .collect::<Vec<_>>();
+        let problem = serde_json::json!({
+          "type": "about:blank",
+          "title": "Invalid request",
+          "status": 400,
+          "detail": detail,
+          "invalidParams": invalid_params,
+        });
+        Ok(
+          hyper::Response::builder()
+            .status(hyper::StatusCode::BAD_REQUEST)
+            .header(hyper::header::CONTENT_TYPE, "application/problem+json")
+            .body(hyper::Body::from(problem.to_string()))?,
+        )
+      }
+
+      #decode_cookie
+
+      fn empty_response(status: u16) -> Result<hyper::Response<hyper::Body>> {
+        Ok(
+          hyper::Response::builder()
+            .status(status)
+            .body(hyper::Body::empty())?,
+        )
+      }
+
+      /// The first of `offered` media types with the highest quality in `Accept` header, the
+      /// first offered one if request has no preference.
+      fn negotiate(accept: Option<&str>, offered: &[&'static str]) -> Option<&'static str> {
+        let accept = match accept.filter(|accept| !accept.trim().is_empty()) {
+          Some(accept) => accept,
+          None => return offered.first().copied(),
+        };
+        let mut ranges = accept
+          .split(',')
+          .map(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let media_range = parts.next().unwrap_or_default();
+            let quality = parts
+              .filter_map(|part| part.strip_prefix("q="))
+              .find_map(|quality| quality.parse::<f32>().ok())
+              .unwrap_or(1.0);
+            (media_range, quality)
+          })
+          .filter(|(_, quality)| *quality > 0.0)
+          .collect::<Vec<_>>();
+        ranges.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        ranges.iter().find_map(|(media_range, _)| {
+          offered.iter().copied().find(|offered| {
+            *media_range == "*/*"
+              || media_range.eq_ignore_ascii_case(offered)
+              || media_range
+                .strip_suffix("/*")
+                .map_or(false, |kind| offered.split('/').next() == Some(kind))
+          })
+        })
+      }
+
+      /// Body serialized into media type, which request accepts, 406 if there is none
+      fn respond<T: serde::Serialize>(
+        status: u16,
+        accept: &Option<String>,
+        offered: &[&'static str],
+        body: &T,
+      ) -> Result<hyper::Response<hyper::Body>> {
+        let body = match negotiate(accept.as_deref(), offered) {
+          Some(mime @ "application/yaml") => (mime, serde_yaml::to_string(body)?),
+          Some(mime) => (mime, serde_json::to_string(body)?),
+          None => {
+            return Ok(
+              hyper::Response::builder()
+                .status(hyper::StatusCode::NOT_ACCEPTABLE)
+                .body(hyper::Body::from(format!("Response is available as {}", offered.join(", "))))?,
+            )
+          }
+        };
+        Ok(
+          hyper::Response::builder()
+            .status(status)
+            .header(hyper::header::CONTENT_TYPE, body.0)
+            .body(hyper::Body::from(body.1))?,
+        )
+      }
+
+      #(#functions)*
+
+      Router::builder()
+        .data(std::sync::Arc::new(api))
+        #(#routing_instructions)*
+        .build()
+        .unwrap()
+
+      }
+  }
+}
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use convert_case::{Case, Casing};
+use oas3::spec::{Operation, Parameter};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::{generate_types, options::Options, security, utils};
+
+pub(crate) fn collect_params(
+  schema: &oas3::Spec,
+  operation: &Operation,
+  options: &Options,
+) -> Vec<TokenStream> {
+  operation
+    .parameters
+    .iter()
+    .map(|p| p.resolve(schema).unwrap_or_else(|_| Parameter::default()))
+    .map(|param_object| {
+      let name = utils::snake_ident(&param_object.name);
+      let type_name = utils::param_type(&param_object, schema, options);
+      quote!( #name: #type_name)
+    })
+    .collect::<Vec<_>>()
+}
+
+/// Types of response bodies by status code and media type
+pub(crate) fn collect_response_types(
+  schema: &oas3::Spec,
+  operation: &Operation,
+  options: &Options,
+) -> BTreeMap<String, BTreeMap<String, String>> {
+  operation
+    .responses
+    .iter()
+    .flat_map(|(code, req_body)| {
+      req_body.resolve(schema).map(|response| {
+        (
+          code.to_owned(),
+          response
+            .content
+            .iter()
+            .map(|(content_type, media_type)| {
+              let my_type = media_type
+                .schema
+                .as_ref()
+                .expect("Schemas of responses are checked")
+                .resolve(schema)
+                .expect("References are checked");
+              let my_type = generate_types::schema_type(&my_type, schema, options);
+              (content_type.to_owned(), my_type.to_string())
+            })
+            .collect(),
+        )
+      })
+    })
+    .collect()
+}
+
+pub(crate) fn collect_body_params(
+  schema: &oas3::Spec,
+  operation: &Operation,
+  options: &Options,
+) -> Option<TokenStream> {
+  utils::request_body_type(operation, schema).map(|(title, _)| {
+    let var = utils::snake_ident(&title);
+    let tpe = options.titled_type(&title);
+    quote!(#var: #tpe)
+  })
+}
+
+pub(crate) fn code_to_name(code: &str) -> String {
+  format!("Result_{code}")
+}
+
+enum SomeType {
+  EnumOfCodes(Vec<TokenStream>),
+  SingleType(TokenStream),
+  NoReturn,
+}
+
+/// Type of response body, `None` if response has none. Every media type of response has the
+/// same type, it is checked.
+fn create_media_types_result(result_types: &BTreeSet<String>) -> Option<TokenStream> {
+  let mut types = result_types.iter();
+  let type_name = types.next()?;
+  assert!(
+    types.next().is_none(),
+    "Response schemas are checked to be the same for all media types"
+  );
+  Some(type_name.parse().expect("Type is printed from tokens"))
+}
+
+fn create_enum_variants(code_conten_type: &BTreeMap<String, BTreeSet<String>>) -> SomeType {
+  if code_conten_type.len() > 1 {
+    SomeType::EnumOfCodes(
+      code_conten_type
+        .iter()
+        .map(|(code, result_types)| {
+          let variant_name = Ident::new(&code_to_name(code), Span::call_site());
+          if let Some(inner_variant_type) = create_media_types_result(result_types) {
+            quote!(#variant_name (#inner_variant_type))
+          } else {
+            quote!(#variant_name)
+          }
+        })
+        .collect(),
+    )
+  } else if let Some(ident) = code_conten_type
+    .iter()
+    .find(|_| true)
+    .and_then(|(_, result_types)| create_media_types_result(result_types))
+  {
+    SomeType::SingleType(ident)
+  } else {
+    SomeType::NoReturn
+  }
+}
+
+/// Name of enum, which operation returns when it has several response codes
+pub(crate) fn result_type_name(method_name: &str) -> Ident {
+  let type_name = format!("{method_name}_result_type").to_case(Case::Pascal);
+  Ident::new(&type_name, Span::call_site())
+}
+
+pub(crate) fn build_result_type(
+  method_name: &str,
+  code_conten_type: BTreeMap<String, BTreeMap<String, String>>,
+) -> (TokenStream, Option<TokenStream>) {
+  let type_name_ident = result_type_name(method_name);
+  let code_conten_type_set: BTreeMap<String, BTreeSet<String>> = code_conten_type
+    .iter()
+    .map(|(code, map)| {
+      (
+        code.to_owned(),
+        map
+          .values()
+          .map(|type_name| type_name.to_owned())
+          .collect::<BTreeSet<_>>(),
+      )
+    })
+    .collect();
+
+  match create_enum_variants(&code_conten_type_set) {
+    SomeType::EnumOfCodes(variants) => (
+      quote!( #type_name_ident ),
+      Some(quote!(
+          #[derive(Debug, Clone, PartialEq)]
+          pub enum #type_name_ident {
+            #(#variants),*
+
+          }
+      )),
+    ),
+    SomeType::SingleType(result_type) => (quote!(#result_type), None),
+
+    SomeType::NoReturn => (quote!(()), None),
+  }
+}
+
+pub fn run(schema: &oas3::Spec, options: &Options) -> TokenStream {
+  let title = options.trait_ident(schema);
+  let (methods, result_types) = schema
+    .paths
+    .values()
+    .flat_map(|item| {
+      item
+        .methods()
+        .into_iter()
+        .map(move |(_, op)| {
+          let method_name = op.operation_id.as_ref().expect("Operation ids are checked");
+          let method_name_ident = Ident::new(&method_name.to_case(Case::Snake), Span::call_site());
+          let mut params = collect_params(schema, op, options);
+          if !security::operation_schemes(op, schema).is_empty() {
+            params.insert(0, quote!(user: Self::User));
+          }
+          params.extend(collect_body_params(schema, op, options));
+          let (result, _result_type) =
+            build_result_type(method_name, collect_response_types(schema, op, options));
+          (
+            quote! {async fn #method_name_ident(&self, #(#params),*) -> Result<#result, Error>;},
+            _result_type,
+          )
+        })
+        .collect::<Vec<_>>()
+    })
+    .unzip::<_, _, Vec<_>, Vec<_>>();
+
+  let credentials = security::credentials_enum(schema);
+  let authenticate = credentials.as_ref().map(|_| {
+    quote! {
+      /// Authenticated user, which is passed into secured operations
+      type User: Send + Sync;
+
+      /// Finds user by credentials from request, `None` makes router answer with 401
+      async fn authenticate(&self, credentials: Credentials) -> Result<Option<Self::User>, Error>;
+    }
+  });
+
+  quote! {
+
+    use async_trait::async_trait;
+    use super::types::*;
+
+    /// Error of api implementation, router answers with internal server error on it
+    pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    #(#result_types) *
+
+    #credentials
+
+    #[async_trait]
+    pub trait #title: Send + Sync + 'static {
+      #authenticate
+      #(#methods)*
+    }
+  }
+}
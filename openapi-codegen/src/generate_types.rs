@@ -0,0 +1,641 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use convert_case::{Case, Casing};
+use oas3::spec::{Discriminator, ObjectOrReference, SchemaType};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{
+  options::Options,
+  problems::{Location, Problem},
+  utils,
+};
+
+/// Schemas with titles, reachable from the spec. Each title becomes single generated type,
+/// however many times the schema is referenced. Inline enums get title from the object and
+/// property they are declared in, like `FoodItemLinkType`.
+pub(crate) struct NamedSchemas<'a> {
+  spec: &'a oas3::Spec,
+  options: &'a Options,
+  schemas: BTreeMap<String, (oas3::Schema, Location)>,
+  problems: Vec<Problem>,
+  uses_base64_bytes: bool,
+}
+
+/// Schemas of the spec, which become Rust types
+pub(crate) struct CollectedSchemas {
+  /// Named schemas by their titles
+  pub schemas: BTreeMap<String, oas3::Schema>,
+  /// Problems, which keep schemas from becoming types
+  pub problems: Vec<Problem>,
+  /// Some string of `byte` format becomes `Base64Bytes`, so the type is generated
+  pub uses_base64_bytes: bool,
+}
+
+impl<'a> NamedSchemas<'a> {
+  /// Named schemas with problems of all schemas reachable from `root` of the spec
+  pub(crate) fn collect(
+    spec: &'a oas3::Spec,
+    root: &Location,
+    options: &'a Options,
+  ) -> CollectedSchemas {
+    let mut named = NamedSchemas {
+      spec,
+      options,
+      schemas: BTreeMap::new(),
+      problems: Vec::new(),
+      uses_base64_bytes: false,
+    };
+    for (name, schema) in spec
+      .components
+      .iter()
+      .flat_map(|components| components.schemas.iter())
+    {
+      let at = root.join("components").join("schemas").join(name);
+      named.visit_ref(schema, &at, None);
+    }
+    for (path, method, op) in spec.operations() {
+      let at = root
+        .join("paths")
+        .join(path)
+        .join(method.as_str().to_lowercase());
+      for (index, param) in op.parameters.iter().enumerate() {
+        if let Some(schema) = param.resolve(spec).ok().and_then(|param| param.schema) {
+          let at = at.join("parameters").join(index).join("schema");
+          named.visit_ref(&schema, &at, None);
+        }
+      }
+      let request_body = op
+        .request_body
+        .as_ref()
+        .and_then(|rb| rb.resolve(spec).ok());
+      for (mime, media) in request_body.iter().flat_map(|rb| rb.content.iter()) {
+        if let Some(schema) = media.schema.as_ref() {
+          let at = at
+            .join("requestBody")
+            .join("content")
+            .join(mime)
+            .join("schema");
+          named.visit_ref(schema, &at, None);
+        }
+      }
+      for (code, response) in op.responses.iter() {
+        let response = response.resolve(spec).ok();
+        for (mime, media) in response.iter().flat_map(|response| response.content.iter()) {
+          if let Some(schema) = media.schema.as_ref() {
+            let at = at
+              .join("responses")
+              .join(code)
+              .join("content")
+              .join(mime)
+              .join("schema");
+            named.visit_ref(schema, &at, None);
+          }
+        }
+      }
+    }
+    let schemas = named
+      .schemas
+      .into_iter()
+      .map(|(title, (schema, _))| (title, schema))
+      .collect();
+    CollectedSchemas {
+      schemas,
+      problems: named.problems,
+      uses_base64_bytes: named.uses_base64_bytes,
+    }
+  }
+
+  fn problem(&mut self, at: &Location, message: impl Into<String>) {
+    self.problems.push(Problem::new(at, message));
+  }
+
+  fn visit_ref(
+    &mut self,
+    schema: &ObjectOrReference<oas3::Schema>,
+    at: &Location,
+    name_hint: Option<String>,
+  ) {
+    let (resolved, at) = match schema {
+      ObjectOrReference::Ref { ref_path } => match schema.resolve(self.spec) {
+        Ok(resolved) => (resolved, at.reference(ref_path)),
+        Err(err) => {
+          return self.problem(at, format!("Reference {ref_path} is not resolved: {err}"))
+        }
+      },
+      ObjectOrReference::Object(inline) => (inline.clone(), at.clone()),
+    };
+    self.visit(&named(resolved, name_hint.clone()), &at, name_hint);
+  }
+
+  fn visit(&mut self, schema: &oas3::Schema, at: &Location, name_hint: Option<String>) {
+    let flattened = match flatten_all_of(schema, self.spec) {
+      Ok(flattened) => flattened,
+      Err(message) => return self.problem(at, message),
+    };
+    if flattened.schema_type == Some(SchemaType::String)
+      && flattened.format.as_deref() == Some("byte")
+      && self.options.format_type("byte").is_none()
+    {
+      self.uses_base64_bytes = true;
+    }
+    if let Some(title) = &flattened.title {
+      // Schema of user supplied type is not generated, neither are the ones it refers to
+      if self.options.titles.contains_key(title) {
+        return;
+      }
+      match self.schemas.get(title) {
+        Some((known, _)) if *known == flattened => return,
+        Some((_, known_at)) => {
+          let message = format!("Title {title} is already used by different schema at {known_at}");
+          return self.problem(at, message);
+        }
+        None => {
+          self
+            .schemas
+            .insert(title.clone(), (flattened.clone(), at.clone()));
+        }
+      }
+      let type_name = self.options.type_name(title);
+      if !utils::is_ident(&type_name) {
+        self.problem(
+          at,
+          format!("Type name {type_name:?} of title {title:?} is not valid"),
+        );
+      }
+    }
+    self.check(&flattened, at);
+
+    for (name, property) in flattened.properties.iter() {
+      let property_at = at.join("properties").join(name);
+      self.visit_ref(property, &property_at, property_name_hint(&flattened, name));
+    }
+    if let Some(items) = flattened.items.as_deref() {
+      self.visit_ref(items, &at.join("items"), items_name_hint(name_hint));
+    }
+    if let Some(additional_properties) = flattened.additional_properties.as_deref() {
+      self.visit_ref(
+        additional_properties,
+        &at.join("additionalProperties"),
+        None,
+      );
+    }
+    // Properties of inline parts are visited as properties of the flattened schema.
+    for (index, part) in schema.all_of.iter().enumerate() {
+      if let ObjectOrReference::Ref { .. } = part {
+        self.visit_ref(part, &at.join("allOf").join(index), None);
+      }
+    }
+    let keyword = if flattened.one_of.is_empty() {
+      "anyOf"
+    } else {
+      "oneOf"
+    };
+    for (index, alternative) in alternatives(&flattened).iter().enumerate() {
+      let alternative_at = at.join(keyword).join(index);
+      self.visit_ref(
+        alternative,
+        &alternative_at,
+        variant_name_hint(&flattened, index),
+      );
+    }
+  }
+
+  /// Problems, which keep schema from becoming Rust type
+  fn check(&mut self, schema: &oas3::Schema, at: &Location) {
+    let is_composition = !alternatives(schema).is_empty();
+    match &schema.schema_type {
+      _ if is_composition && schema.title.is_none() => {
+        self.problem(at, "Schema with oneOf or anyOf needs title")
+      }
+      Some(SchemaType::Object) if schema.title.is_none() => {
+        self.problem(at, "Object schema needs title")
+      }
+      Some(SchemaType::String) if is_enum(schema) => {
+        if schema.title.is_none() {
+          self.problem(
+            at,
+            "Enum needs title, when it is not a property of titled object",
+          );
+        }
+        if let Err(message) = utils::enum_variants(&schema.enum_values) {
+          self.problem(&at.join("enum"), message);
+        }
+      }
+      Some(SchemaType::Array) if schema.items.is_none() => {
+        self.problem(at, "Array schema needs items")
+      }
+      None if !is_composition => self.problem(at, "Schema needs type"),
+      _ => {}
+    }
+  }
+}
+
+fn is_enum(schema: &oas3::Schema) -> bool {
+  schema.schema_type == Some(SchemaType::String) && !schema.enum_values.is_empty()
+}
+
+/// Schemas of `oneOf` or `anyOf`, the payload is one of them
+fn alternatives(schema: &oas3::Schema) -> &[ObjectOrReference<oas3::Schema>] {
+  if schema.one_of.is_empty() {
+    &schema.any_of
+  } else {
+    &schema.one_of
+  }
+}
+
+/// Object with properties and required lists of all `allOf` schemas merged into its own.
+/// Inline enums keep titles they have in the schema declaring them, so `Meal.tag` has the same
+/// `AddMealRequestTag` type as `AddMealRequest.tag`.
+fn flatten_all_of(schema: &oas3::Schema, spec: &oas3::Spec) -> Result<oas3::Schema, String> {
+  let mut flattened = schema.clone();
+  if schema.all_of.is_empty() {
+    return Ok(flattened);
+  }
+  flattened.all_of.clear();
+  flattened.schema_type = Some(SchemaType::Object);
+  for part in schema.all_of.iter() {
+    let part = part
+      .resolve(spec)
+      .map_err(|err| format!("Schema of allOf is not resolved: {err}"))?;
+    let part = flatten_all_of(&part, spec)?;
+    for (name, property) in part.properties.iter() {
+      let property = match property {
+        ObjectOrReference::Object(inline) => {
+          ObjectOrReference::Object(named(inline.clone(), property_name_hint(&part, name)))
+        }
+        reference => reference.clone(),
+      };
+      flattened.properties.entry(name.clone()).or_insert(property);
+    }
+    for name in part.required {
+      if !flattened.required.contains(&name) {
+        flattened.required.push(name);
+      }
+    }
+  }
+  Ok(flattened)
+}
+
+/// Title for inline enum declared as property of titled object
+fn property_name_hint(schema: &oas3::Schema, property: &str) -> Option<String> {
+  schema
+    .title
+    .as_ref()
+    .map(|title| format!("{title}{}", property.to_case(Case::Pascal)))
+}
+
+/// Title for inline enum of array items, like `TagsItem`
+fn items_name_hint(name_hint: Option<String>) -> Option<String> {
+  name_hint.map(|name_hint| format!("{name_hint}Item"))
+}
+
+/// Title for inline enum alternative of `oneOf` or `anyOf`, like `ValueVariant1`
+fn variant_name_hint(schema: &oas3::Schema, index: usize) -> Option<String> {
+  schema
+    .title
+    .as_ref()
+    .map(|title| format!("{title}Variant{}", index + 1))
+}
+
+/// Gives title to inline enum, so it is generated as named type
+fn named(mut schema: oas3::Schema, name_hint: Option<String>) -> oas3::Schema {
+  if schema.title.is_none() && is_enum(&schema) {
+    schema.title = name_hint;
+  }
+  schema
+}
+
+/// Rust type of resolved schema, which is not a property, like the one of response body
+pub(crate) fn schema_type(
+  schema: &oas3::Schema,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> TokenStream {
+  resolve_schema_type(schema, None, spec, options)
+}
+
+fn resolve_schema_type(
+  schema: &oas3::Schema,
+  name_hint: Option<String>,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> TokenStream {
+  let is_composition = !(schema.all_of.is_empty() && alternatives(schema).is_empty());
+  match schema.schema_type {
+    _ if schema
+      .title
+      .as_ref()
+      .is_some_and(|title| options.titles.contains_key(title)) =>
+    {
+      options.titled_type(schema.title.as_ref().unwrap())
+    }
+    _ if is_composition && schema.title.is_some() => {
+      options.titled_type(schema.title.as_ref().unwrap())
+    }
+    Some(SchemaType::Object) => {
+      let title = schema
+        .title
+        .as_ref()
+        .expect("Titles of object schemas are checked");
+      options.titled_type(title)
+    }
+    Some(SchemaType::String) if is_enum(schema) => {
+      let title = schema.title.as_ref().expect("Titles of enums are checked");
+      options.titled_type(title)
+    }
+    Some(SchemaType::String | SchemaType::Number | SchemaType::Integer | SchemaType::Boolean) => {
+      utils::primitive_type(schema, options).unwrap()
+    }
+    Some(SchemaType::Array) => {
+      let items = schema
+        .items
+        .as_ref()
+        .expect("Items of arrays are checked")
+        .resolve(spec)
+        .expect("References are checked");
+      let items_name_hint = items_name_hint(name_hint);
+      let items = named(items, items_name_hint.clone());
+      let item_type = resolve_schema_type(&items, items_name_hint, spec, options);
+      quote!(Vec<#item_type>)
+    }
+    None => unreachable!("Schema types are checked"),
+  }
+}
+
+/// Fields of struct for object schema. Properties, which are discriminator tags of the enums the
+/// struct is variant of, are skipped: serde reads and writes them with the enum.
+fn extract_struct_fields(
+  schema: &oas3::Schema,
+  tags: &BTreeSet<String>,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> Vec<TokenStream> {
+  schema
+    .properties
+    .iter()
+    .filter(|(name, _)| !tags.contains(*name))
+    .map(|(name, field_type)| {
+      let field_name = utils::snake_ident(name);
+      let field_schema = field_type.resolve(spec).ok();
+      let name_hint = property_name_hint(schema, name);
+      let field_schema = &named(field_schema.unwrap(), name_hint.clone());
+      let type_name = resolve_schema_type(field_schema, name_hint, spec, options);
+      let rename = (field_name.to_string().trim_start_matches("r#") != name)
+        .then(|| quote!(#[serde(rename = #name)]));
+
+      // Missing optional field is skipped on serialization, while nullable required one is
+      // written as `null`.
+      let required = schema.required.contains(name);
+      let nullable = field_schema.nullable.unwrap_or(false);
+      match (required, nullable) {
+        (true, false) => quote!(#rename pub #field_name: #type_name),
+        (true, true) => quote!(#rename pub #field_name: Option<#type_name>),
+        (false, _) => quote!(
+          #rename
+          #[serde(default, skip_serializing_if = "Option::is_none")]
+          pub #field_name: Option<#type_name>
+        ),
+      }
+    })
+    .collect()
+}
+
+/// Enum of string values. Values are kept as they are on the wire, while variants are pascal
+/// cased, so `Omega-3` becomes `Omega3`.
+fn enum_to_type(schema: &oas3::Schema, options: &Options) -> TokenStream {
+  let title = schema.title.as_ref().unwrap();
+  let type_name = options.type_ident(title);
+  let values = &schema.enum_values;
+  let variants = utils::enum_variants(values).expect("Enum values are checked");
+  let unknown = format!("Unknown {title} value: {{}}");
+  let derives = options.derives(quote!(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize
+  ));
+  quote!(
+    #derives
+    pub enum #type_name {
+      #(#[serde(rename = #values)] #variants), *
+    }
+
+    impl std::fmt::Display for #type_name {
+      fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+          #(Self::#variants => f.write_str(#values)), *
+        }
+      }
+    }
+
+    impl std::str::FromStr for #type_name {
+      type Err = String;
+
+      fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+          #(#values => Ok(Self::#variants),)*
+          _ => Err(format!(#unknown, value)),
+        }
+      }
+    }
+  )
+}
+
+/// Value of discriminator property for alternative: key of discriminator mapping pointing to
+/// the schema, or its title, when mapping has none
+fn discriminator_value(
+  discriminator: &Discriminator,
+  alternative: &ObjectOrReference<oas3::Schema>,
+  title: &str,
+) -> String {
+  let reference = match alternative {
+    ObjectOrReference::Ref { ref_path } => Some(ref_path.as_str()),
+    ObjectOrReference::Object(_) => None,
+  };
+  discriminator
+    .mapping
+    .iter()
+    .find(|(_, target)| {
+      Some(target.as_str()) == reference
+        || target.as_str() == title
+        || target.ends_with(&format!("/{title}"))
+    })
+    .map(|(value, _)| value.clone())
+    .unwrap_or_else(|| title.to_owned())
+}
+
+/// Enum of `oneOf` or `anyOf` alternatives. With discriminator, it is tagged by discriminator
+/// property, otherwise the first alternative, payload matches, is taken.
+fn alternatives_to_type(
+  schema: &oas3::Schema,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> TokenStream {
+  let title = schema.title.as_ref().unwrap();
+  let type_name = options.type_ident(title);
+  let variants = alternatives(schema)
+    .iter()
+    .enumerate()
+    .map(|(index, alternative)| {
+      let name_hint = variant_name_hint(schema, index);
+      let resolved = alternative.resolve(spec).expect("References are checked");
+      let resolved = named(resolved, name_hint.clone());
+      let variant_name = resolved
+        .title
+        .clone()
+        .unwrap_or_else(|| format!("Variant{}", index + 1));
+      let variant = utils::pascal_ident(&variant_name);
+      let variant_type = resolve_schema_type(&resolved, name_hint, spec, options);
+      let rename = schema.discriminator.as_ref().map(|discriminator| {
+        let value = discriminator_value(discriminator, alternative, &variant_name);
+        quote!(#[serde(rename = #value)])
+      });
+      quote!(#rename #variant(#variant_type))
+    })
+    .collect::<Vec<_>>();
+  let tag = match &schema.discriminator {
+    Some(discriminator) => {
+      let property_name = &discriminator.property_name;
+      quote!(#[serde(tag = #property_name)])
+    }
+    None => quote!(#[serde(untagged)]),
+  };
+  let derives = options.derives(quote!(
+    Debug,
+    Clone,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize
+  ));
+  quote!(
+    #derives
+    #tag
+    pub enum #type_name {
+      #(#variants), *
+    }
+  )
+}
+
+fn schema_to_type(
+  schema: &oas3::Schema,
+  tags: &BTreeSet<String>,
+  spec: &oas3::Spec,
+  options: &Options,
+) -> Option<TokenStream> {
+  if !alternatives(schema).is_empty() {
+    Some(alternatives_to_type(schema, spec, options))
+  } else if let Some(SchemaType::Object) = &schema.schema_type {
+    let type_name = options.type_ident(schema.title.as_ref().unwrap());
+    let struct_fields = extract_struct_fields(schema, tags, spec, options);
+    let derives = options.derives(quote!(
+      Debug,
+      Clone,
+      PartialEq,
+      serde::Serialize,
+      serde::Deserialize
+    ));
+    Some(quote!(
+    #derives
+    pub struct #type_name {
+      #(#struct_fields), *
+
+    }))
+  } else if is_enum(schema) {
+    Some(enum_to_type(schema, options))
+  } else {
+    None
+  }
+}
+
+/// Bytes of `byte` format, which are base64 encoded strings on the wire. Generated only when the
+/// spec has such strings, so `base64` crate, 0.21 or newer with its engines, is needed only then.
+fn base64_bytes_type() -> TokenStream {
+  quote!(
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+    pub struct Base64Bytes(pub Vec<u8>);
+
+    impl serde::Serialize for Base64Bytes {
+      fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+      }
+    }
+
+    impl<'de> serde::Deserialize<'de> for Base64Bytes {
+      fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = <String as serde::Deserialize>::deserialize(deserializer)?;
+        encoded.parse().map_err(serde::de::Error::custom)
+      }
+    }
+
+    impl std::fmt::Display for Base64Bytes {
+      fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use base64::Engine as _;
+        f.write_str(&base64::engine::general_purpose::STANDARD.encode(&self.0))
+      }
+    }
+
+    impl std::str::FromStr for Base64Bytes {
+      type Err = base64::DecodeError;
+
+      fn from_str(encoded: &str) -> Result<Self, Self::Err> {
+        use base64::Engine as _;
+        base64::engine::general_purpose::STANDARD
+          .decode(encoded)
+          .map(Base64Bytes)
+      }
+    }
+  )
+}
+
+/// Discriminator properties of enums by titles of their variants
+fn variant_tags(
+  schemas: &BTreeMap<String, oas3::Schema>,
+  spec: &oas3::Spec,
+) -> BTreeMap<String, BTreeSet<String>> {
+  let mut tags: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+  for schema in schemas.values() {
+    let discriminator = match &schema.discriminator {
+      Some(discriminator) => discriminator,
+      None => continue,
+    };
+    let titles = alternatives(schema)
+      .iter()
+      .filter_map(|alternative| alternative.resolve(spec).ok()?.title);
+    for title in titles {
+      tags
+        .entry(title)
+        .or_default()
+        .insert(discriminator.property_name.clone());
+    }
+  }
+  tags
+}
+
+pub fn run(spec: &oas3::Spec, root: &Location, options: &Options) -> TokenStream {
+  let CollectedSchemas {
+    schemas,
+    uses_base64_bytes,
+    ..
+  } = NamedSchemas::collect(spec, root, options);
+  let tags = variant_tags(&schemas, spec);
+  let no_tags = BTreeSet::new();
+  let types = schemas
+    .iter()
+    .filter_map(|(title, schema)| {
+      let tags = tags.get(title).unwrap_or(&no_tags);
+      schema_to_type(schema, tags, spec, options)
+    })
+    .collect::<TokenStream>();
+  let base64_bytes = uses_base64_bytes.then(base64_bytes_type);
+  quote! {
+    #types
+    #base64_bytes
+  }
+}
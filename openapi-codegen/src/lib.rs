@@ -0,0 +1,260 @@
+//! Rust code generation from OpenAPI spec: types of schemas, trait of the api, router, which
+//! serves the trait, and client of the api.
+//!
+//! Generated modules refer to each other as `super::types` and `super::traits`, so they must be
+//! siblings. `types!` macro of `openapi-types-generator` puts them into the module, it is called
+//! in, while `openapi-codegen` binary writes them into files of a directory with `mod.rs`.
+
+use std::path::Path;
+
+use oas3::OpenApiV3Spec;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+mod check;
+mod generate_client;
+mod generate_routing;
+mod generate_trait;
+mod generate_types;
+mod options;
+mod problems;
+mod security;
+pub(crate) mod utils;
+
+pub use options::{Options, MODULES};
+pub use problems::{Location, Problem};
+
+/// Reads spec with all files it references
+pub fn read_spec<P>(path: P) -> Result<OpenApiV3Spec, String>
+where
+  P: AsRef<Path>,
+{
+  let root = path.as_ref().parent();
+  let file = path.as_ref().file_name().and_then(|file| file.to_str());
+  if let (Some(root), Some(file)) = (root, file) {
+    oas3::from_path_dir(root.display().to_string(), file).map_err(|err| {
+      format!(
+        "\"Cannot parse file: {} Error: {err}\"",
+        path.as_ref().display()
+      )
+    })
+  } else {
+    Err("Cannot get filename and path correctly".into())
+  }
+}
+
+/// Generated modules of the api, the ones options leave out are `None`
+pub struct Modules {
+  pub types: Option<TokenStream>,
+  pub traits: Option<TokenStream>,
+  pub router: Option<TokenStream>,
+  pub client: Option<TokenStream>,
+}
+
+impl Modules {
+  /// Modules for spec, read from `file`, or all problems, which keep them from being generated
+  pub fn generate(
+    spec: &OpenApiV3Spec,
+    file: &str,
+    options: &Options,
+  ) -> Result<Self, Vec<Problem>> {
+    let problems = check::check(spec, file, options);
+    if !problems.is_empty() {
+      return Err(problems);
+    }
+    let root = Location::root(spec, file);
+    Ok(Modules {
+      types: options
+        .has_module("types")
+        .then(|| generate_types::run(spec, &root, options)),
+      traits: options
+        .has_module("traits")
+        .then(|| generate_trait::run(spec, options)),
+      router: options
+        .has_module("router")
+        .then(|| generate_routing::run(spec, options)),
+      client: options
+        .has_module("client")
+        .then(|| generate_client::run(spec, options)),
+    })
+  }
+
+  /// Generated modules by their names
+  pub fn named(&self) -> Vec<(&'static str, &TokenStream)> {
+    MODULES
+      .into_iter()
+      .zip([&self.types, &self.traits, &self.router, &self.client])
+      .filter_map(|(name, tokens)| Some((name, tokens.as_ref()?)))
+      .collect()
+  }
+
+  /// All modules declared inline, the way `types!` macro expands
+  pub fn into_tokens(self) -> TokenStream {
+    self
+      .named()
+      .into_iter()
+      .map(|(name, tokens)| {
+        let name = Ident::new(name, Span::call_site());
+        quote! {
+          pub mod #name {
+            #tokens
+          }
+        }
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn generates_valid_modules_for_habfoo_api() {
+    let file = "../habfoo-api/api/root.yaml";
+    let spec = read_spec(file).unwrap();
+    let modules = match Modules::generate(&spec, file, &Options::default()) {
+      Ok(modules) => modules,
+      Err(problems) => panic!("Spec has problems: {problems:#?}"),
+    };
+    for (name, tokens) in modules.named() {
+      if let Err(err) = syn::parse2::<syn::File>(tokens.clone()) {
+        panic!("Module {name} is not valid Rust: {err}");
+      }
+    }
+  }
+
+  #[test]
+  fn names_types_by_options() {
+    let file = "../habfoo-api/api/root.yaml";
+    let spec = read_spec(file).unwrap();
+    let options = Options {
+      modules: vec!["types".into(), "traits".into()],
+      type_prefix: "Api".into(),
+      titles: [(
+        "NutrientKind".to_owned(),
+        syn::parse_quote!(crate::NutrientKind),
+      )]
+      .into(),
+      trait_name: Some("Habfoo".into()),
+      ..Options::default()
+    };
+    let modules = Modules::generate(&spec, file, &options).unwrap();
+    let types = modules.types.unwrap().to_string();
+    assert!(types.contains("pub struct ApiMeal"));
+    assert!(!types.contains("pub enum ApiNutrientKind"));
+    assert!(types.contains("crate :: NutrientKind"));
+    assert!(modules
+      .traits
+      .unwrap()
+      .to_string()
+      .contains("pub trait Habfoo"));
+    assert!(modules.router.is_none() && modules.client.is_none());
+  }
+
+  #[test]
+  fn encodes_bytes_with_base64_engine() {
+    let yaml = r#"
+openapi: 3.0.0
+info:
+  title: Files
+  version: 1.0.0
+paths: {}
+components:
+  schemas:
+    File:
+      title: File
+      type: object
+      properties:
+        content:
+          type: string
+          format: byte
+"#;
+    let spec = oas3::from_reader(yaml.as_bytes()).unwrap();
+    let options = Options {
+      modules: vec!["types".into()],
+      ..Options::default()
+    };
+    let types = Modules::generate(&spec, "files.yaml", &options)
+      .unwrap()
+      .types
+      .unwrap();
+    syn::parse2::<syn::File>(types.clone()).unwrap();
+    let types = types.to_string();
+    assert!(types.contains("pub struct Base64Bytes"));
+    assert!(types.contains("base64 :: engine :: general_purpose :: STANDARD"));
+  }
+
+  #[test]
+  fn generates_base64_bytes_only_for_byte_format() {
+    let yaml = r#"
+openapi: 3.0.0
+info:
+  title: Notes
+  version: 1.0.0
+paths: {}
+components:
+  schemas:
+    Base64BytesNote:
+      title: Base64BytesNote
+      type: object
+      properties:
+        text:
+          type: string
+"#;
+    let spec = oas3::from_reader(yaml.as_bytes()).unwrap();
+    let types = Modules::generate(&spec, "notes.yaml", &Options::default())
+      .unwrap()
+      .types
+      .unwrap()
+      .to_string();
+    assert!(types.contains("pub struct Base64BytesNote"));
+    assert!(!types.contains("pub struct Base64Bytes "));
+  }
+
+  #[test]
+  fn reports_unsupported_response_media_type() {
+    let yaml = r#"
+openapi: 3.0.0
+info:
+  title: Notes
+  version: 1.0.0
+paths:
+  /note:
+    get:
+      operationId: getNote
+      responses:
+        200:
+          description: Note text
+          content:
+            text/plain:
+              schema:
+                type: string
+"#;
+    let spec = oas3::from_reader(yaml.as_bytes()).unwrap();
+    let problems = match Modules::generate(&spec, "notes.yaml", &Options::default()) {
+      Ok(_) => panic!("Response of text/plain is not reported"),
+      Err(problems) => problems,
+    };
+    assert_eq!(problems.len(), 1);
+    assert_eq!(
+      problems[0].location.to_string(),
+      "notes.yaml#/paths/~1note/get/responses/200/content/text~1plain"
+    );
+  }
+
+  #[test]
+  fn reports_empty_security_requirement() {
+    let file = "../habfoo-api/api/root.yaml";
+    let mut spec = read_spec(file).unwrap();
+    spec.security = vec![Default::default()];
+    let problems = match Modules::generate(&spec, file, &Options::default()) {
+      Ok(_) => panic!("Empty security requirement is not reported"),
+      Err(problems) => problems,
+    };
+    assert!(problems.iter().any(|problem| {
+      problem.location.to_string() == "../habfoo-api/api/root.yaml#/security/0"
+        && problem.message.starts_with("Empty security requirement")
+    }));
+  }
+}
@@ -0,0 +1,135 @@
+//! Writes generated modules of the api into files:
+//!
+//! ```sh
+//! openapi-codegen habfoo-api/api/root.yaml habfoo-server-mongo/src/api
+//! ```
+//!
+//! Directory gets `types.rs`, `traits.rs`, `router.rs`, `client.rs` and `mod.rs`, which declares
+//! them, all formatted with `rustfmt`. With `--check` nothing is written, the command fails if
+//! any of files differs from what would be generated.
+
+use std::{
+  fs,
+  io::Write,
+  path::Path,
+  process::{Command, ExitCode, Stdio},
+};
+
+use openapi_codegen::{read_spec, Modules, Options};
+
+const USAGE: &str = "Usage: openapi-codegen <spec.yaml> <output-dir> [--check]";
+
+/// Code formatted by `rustfmt`, it is taken from `RUSTFMT` variable, if there is one
+fn rustfmt(code: &str) -> Result<String, String> {
+  let rustfmt = std::env::var("RUSTFMT").unwrap_or_else(|_| "rustfmt".into());
+  let mut child = Command::new(&rustfmt)
+    .args(["--edition", "2021"])
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|err| format!("Cannot run {rustfmt}: {err}"))?;
+  child
+    .stdin
+    .take()
+    .expect("Stdin of rustfmt is piped")
+    .write_all(code.as_bytes())
+    .map_err(|err| format!("Cannot write code to {rustfmt}: {err}"))?;
+  let output = child
+    .wait_with_output()
+    .map_err(|err| format!("Cannot read code from {rustfmt}: {err}"))?;
+  if output.status.success() {
+    String::from_utf8(output.stdout).map_err(|err| err.to_string())
+  } else {
+    Err(String::from_utf8_lossy(&output.stderr).into_owned())
+  }
+}
+
+/// Contents of generated files by their names
+fn generate(spec_path: &str) -> Result<Vec<(String, String)>, String> {
+  let spec = read_spec(spec_path)?;
+  let modules = Modules::generate(&spec, spec_path, &Options::default()).map_err(|problems| {
+    problems
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<_>>()
+      .join("\n")
+  })?;
+  // Only file name goes to the header, so it doesn't depend on directory the command is run from
+  let spec_name = Path::new(spec_path)
+    .file_name()
+    .map_or_else(|| spec_path.into(), |name| name.to_string_lossy());
+  let header = format!("// Generated by openapi-codegen from {spec_name}, do not edit.\n\n");
+  let mut files = Vec::new();
+  let mut declarations = String::new();
+  for (name, tokens) in modules.named() {
+    let code =
+      rustfmt(&tokens.to_string()).map_err(|err| format!("Cannot format module {name}: {err}"))?;
+    files.push((format!("{name}.rs"), format!("{header}{code}")));
+    declarations.push_str(&format!("pub mod {name};\n"));
+  }
+  files.push(("mod.rs".into(), format!("{header}{declarations}")));
+  Ok(files)
+}
+
+fn write(output_dir: &Path, files: &[(String, String)]) -> Result<(), String> {
+  fs::create_dir_all(output_dir)
+    .map_err(|err| format!("Cannot create {}: {err}", output_dir.display()))?;
+  for (name, code) in files {
+    let path = output_dir.join(name);
+    fs::write(&path, code).map_err(|err| format!("Cannot write {}: {err}", path.display()))?;
+    println!("Written {}", path.display());
+  }
+  Ok(())
+}
+
+/// Files, which are missing or differ from generated ones
+fn stale(output_dir: &Path, files: &[(String, String)]) -> Vec<String> {
+  files
+    .iter()
+    .map(|(name, code)| (output_dir.join(name), code))
+    .filter(|(path, code)| fs::read_to_string(path).ok().as_ref() != Some(*code))
+    .map(|(path, _)| path.display().to_string())
+    .collect()
+}
+
+fn main() -> ExitCode {
+  let args = std::env::args().skip(1).collect::<Vec<_>>();
+  let (spec_path, output_dir, check) = match args.iter().map(String::as_str).collect::<Vec<_>>()[..]
+  {
+    [spec_path, output_dir] => (spec_path, output_dir, false),
+    [spec_path, output_dir, "--check"] => (spec_path, output_dir, true),
+    _ => {
+      eprintln!("{USAGE}");
+      return ExitCode::from(2);
+    }
+  };
+  let output_dir = Path::new(output_dir);
+
+  let files = match generate(spec_path) {
+    Ok(files) => files,
+    Err(err) => {
+      eprintln!("{err}");
+      return ExitCode::FAILURE;
+    }
+  };
+  if check {
+    let stale = stale(output_dir, &files);
+    for path in stale.iter() {
+      eprintln!("{path} is not up to date with {spec_path}");
+    }
+    if stale.is_empty() {
+      ExitCode::SUCCESS
+    } else {
+      ExitCode::FAILURE
+    }
+  } else {
+    match write(output_dir, &files) {
+      Ok(()) => ExitCode::SUCCESS,
+      Err(err) => {
+        eprintln!("{err}");
+        ExitCode::FAILURE
+      }
+    }
+  }
+}
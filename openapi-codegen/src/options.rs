@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+
+/// Names of modules, which can be generated, in order of their dependencies
+pub const MODULES: [&str; 4] = ["types", "traits", "router", "client"];
+
+/// What is generated and how generated types are named
+#[derive(Debug, Clone)]
+pub struct Options {
+  /// Modules to generate, all of [`MODULES`] by default
+  pub modules: Vec<String>,
+  /// Prefix of every type, generated for schema, like `Api` for `ApiMeal`
+  pub type_prefix: String,
+  /// Derives of generated types in addition to the ones they always have
+  pub derives: Vec<syn::Path>,
+  /// Types for string formats, they take precedence over built-in ones like `date-time`
+  pub formats: BTreeMap<String, syn::Type>,
+  /// Types for schemas with titles, which are used instead of generated ones
+  pub titles: BTreeMap<String, syn::Type>,
+  /// Name of the api trait, pascal cased title of the spec by default
+  pub trait_name: Option<String>,
+}
+
+impl Default for Options {
+  fn default() -> Self {
+    Options {
+      modules: MODULES.iter().map(|module| module.to_string()).collect(),
+      type_prefix: String::new(),
+      derives: Vec::new(),
+      formats: BTreeMap::new(),
+      titles: BTreeMap::new(),
+      trait_name: None,
+    }
+  }
+}
+
+impl Options {
+  /// Modules are known and each of them is generated with the ones it refers to
+  pub fn check_modules(&self) -> Result<(), String> {
+    for module in self.modules.iter() {
+      let dependencies: &[&str] = match module.as_str() {
+        "types" => &[],
+        "traits" => &["types"],
+        "router" | "client" => &["types", "traits"],
+        _ => {
+          return Err(format!(
+            "Unknown module {module}, expected one of {MODULES:?}"
+          ))
+        }
+      };
+      if let Some(missing) = dependencies
+        .iter()
+        .find(|dependency| !self.has_module(dependency))
+      {
+        return Err(format!("Module {module} needs module {missing}"));
+      }
+    }
+    Ok(())
+  }
+
+  /// Module is generated
+  pub fn has_module(&self, module: &str) -> bool {
+    self.modules.iter().any(|name| name == module)
+  }
+
+  /// Name of type, generated for schema with `title`
+  pub fn type_name(&self, title: &str) -> String {
+    format!("{}{title}", self.type_prefix)
+  }
+
+  /// Identifier of type, generated for schema with `title`
+  pub fn type_ident(&self, title: &str) -> Ident {
+    Ident::new(&self.type_name(title), Span::call_site())
+  }
+
+  /// Type, schema with `title` is referred by: the one given for the title or generated one
+  pub fn titled_type(&self, title: &str) -> TokenStream {
+    match self.titles.get(title) {
+      Some(user_type) => user_type.to_token_stream(),
+      None => {
+        let ident = self.type_ident(title);
+        quote!(#ident)
+      }
+    }
+  }
+
+  /// Type, given for string `format`
+  pub fn format_type(&self, format: &str) -> Option<TokenStream> {
+    self.formats.get(format).map(ToTokens::to_token_stream)
+  }
+
+  /// Derives of generated type, which always has `derives`, without repeating them
+  pub fn derives(&self, derives: TokenStream) -> TokenStream {
+    let always = derives.to_string();
+    let extra = self
+      .derives
+      .iter()
+      .filter(|path| {
+        let path = path.to_token_stream().to_string();
+        !always.split(',').any(|derive| derive.trim() == path)
+      })
+      .collect::<Vec<_>>();
+    quote!(#[derive(#derives #(, #extra)*)])
+  }
+
+  /// Name of the api trait
+  pub fn trait_name(&self, spec: &oas3::Spec) -> String {
+    self
+      .trait_name
+      .clone()
+      .unwrap_or_else(|| spec.info.title.to_case(Case::Pascal))
+  }
+
+  /// Identifier of the api trait
+  pub fn trait_ident(&self, spec: &oas3::Spec) -> Ident {
+    Ident::new(&self.trait_name(spec), Span::call_site())
+  }
+}
@@ -0,0 +1,110 @@
+use std::{fmt, path::Path};
+
+/// Place in the spec: file and JSON pointer into its document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+  root_file: String,
+  root_directory: String,
+  file: String,
+  pointer: String,
+}
+
+impl Location {
+  /// Root of the spec document in `file`
+  pub fn root(spec: &oas3::Spec, file: &str) -> Self {
+    Location {
+      root_file: file.to_owned(),
+      root_directory: spec.root_directory.clone().unwrap_or_default(),
+      file: file.to_owned(),
+      pointer: "#".into(),
+    }
+  }
+
+  /// Location of the child with `token` name or index, escaped as JSON pointer requires
+  pub fn join(&self, token: impl fmt::Display) -> Self {
+    let token = token.to_string().replace('~', "~0").replace('/', "~1");
+    Location {
+      pointer: format!("{}/{token}", self.pointer),
+      ..self.clone()
+    }
+  }
+
+  /// Location, `$ref` points to. Local references are resolved in the root document, file ones
+  /// are relative to its directory.
+  pub fn reference(&self, ref_path: &str) -> Self {
+    let (file, pointer) = ref_path.split_once('#').unwrap_or((ref_path, ""));
+    let file = if file.is_empty() {
+      self.root_file.clone()
+    } else {
+      Path::new(&self.root_directory)
+        .join(file.trim_start_matches("./"))
+        .display()
+        .to_string()
+    };
+    Location {
+      file,
+      pointer: format!("#{pointer}"),
+      ..self.clone()
+    }
+  }
+}
+
+impl fmt::Display for Location {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}{}", self.file, self.pointer)
+  }
+}
+
+/// Something in the spec, code can't be generated for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+  pub location: Location,
+  pub message: String,
+}
+
+impl Problem {
+  pub fn new(location: &Location, message: impl Into<String>) -> Self {
+    Problem {
+      location: location.clone(),
+      message: message.into(),
+    }
+  }
+}
+
+impl fmt::Display for Problem {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.location, self.message)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn escapes_pointer_and_follows_references() {
+    let root = Location {
+      root_file: "api/root.yaml".into(),
+      root_directory: "api".into(),
+      file: "api/root.yaml".into(),
+      pointer: "#".into(),
+    };
+    let body = root
+      .join("paths")
+      .join("/add-meal")
+      .join("post")
+      .join("requestBody");
+    assert_eq!(
+      body.to_string(),
+      "api/root.yaml#/paths/~1add-meal/post/requestBody"
+    );
+    assert_eq!(
+      body.reference("./types/meal.yaml").to_string(),
+      "api/types/meal.yaml#"
+    );
+    assert_eq!(
+      body.reference("#/components/schemas/Meal").to_string(),
+      "api/root.yaml#/components/schemas/Meal"
+    );
+  }
+}
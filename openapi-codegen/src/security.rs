@@ -0,0 +1,160 @@
+use convert_case::{Case, Casing};
+use oas3::spec::{Operation, SecurityScheme};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+/// Security schemes, declared in components, sorted by name
+pub fn security_schemes(spec: &oas3::Spec) -> Vec<(String, SecurityScheme)> {
+  spec
+    .components
+    .iter()
+    .flat_map(|components| components.security_schemes.iter())
+    .map(|(name, scheme)| {
+      let scheme = scheme.resolve(spec).expect("Security schemes are checked");
+      (name.to_owned(), scheme)
+    })
+    .collect()
+}
+
+/// Names of schemes, any of which lets user to run the operation. Requirements, which combine
+/// several schemes at once, are not supported.
+pub fn operation_schemes(op: &Operation, spec: &oas3::Spec) -> Vec<String> {
+  op.security(spec)
+    .iter()
+    .map(|requirement| {
+      let mut names = requirement.keys();
+      match (names.next(), names.next()) {
+        (Some(name), None) => name.to_owned(),
+        _ => unreachable!("Requirements are checked to have single security scheme"),
+      }
+    })
+    .collect()
+}
+
+fn variant_name(scheme_name: &str) -> Ident {
+  Ident::new(&scheme_name.to_case(Case::Pascal), Span::call_site())
+}
+
+/// Enum with a variant for each security scheme, which carries credentials from request
+pub fn credentials_enum(spec: &oas3::Spec) -> Option<TokenStream> {
+  let schemes = security_schemes(spec);
+  if schemes.is_empty() {
+    return None;
+  }
+  let variants = schemes.iter().map(|(name, _)| {
+    let variant = variant_name(name);
+    quote!(#variant(String))
+  });
+  Some(quote! {
+    /// Credentials, found in request for one of security schemes
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Credentials {
+      #(#variants),*
+    }
+  })
+}
+
+fn read_credentials(name: &str, scheme: &SecurityScheme) -> TokenStream {
+  let variant = variant_name(name);
+  let value = match scheme {
+    // Auth scheme names are case insensitive, so `bearer` and `Bearer` are the same
+    SecurityScheme::Http { scheme, .. } => quote! {
+      req
+        .headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(#scheme))
+        .map(|(_, value)| value.trim_start().to_owned())
+    },
+    SecurityScheme::ApiKey { name, location } => match location.as_str() {
+      "header" => quote! {
+        req
+          .headers()
+          .get(#name)
+          .and_then(|value| value.to_str().ok())
+          .map(|value| value.to_owned())
+      },
+      "query" => quote! {
+        req
+          .uri()
+          .query()
+          .and_then(|query| {
+            serde_qs::from_str::<std::collections::HashMap<String, String>>(query).ok()
+          })
+          .and_then(|mut query| query.remove(#name))
+      },
+      "cookie" => quote! {
+        req
+          .headers()
+          .get_all(hyper::header::COOKIE)
+          .iter()
+          .filter_map(|value| value.to_str().ok())
+          .flat_map(|value| value.split(';'))
+          .filter_map(|cookie| cookie.trim().split_once('='))
+          .find(|(cookie, _)| *cookie == #name)
+          .map(|(_, value)| decode_cookie(value))
+      },
+      _ => unreachable!("Api key locations are checked"),
+    },
+    _ => unreachable!("Security schemes are checked"),
+  };
+  quote! {
+    .or_else(|| (#value).map(super::traits::Credentials::#variant))
+  }
+}
+
+/// Challenges of `WWW-Authenticate` header for http schemes. Api keys have no standard challenge,
+/// so they aren't listed.
+fn challenges<'a>(schemes: impl Iterator<Item = &'a SecurityScheme>) -> Option<String> {
+  let challenges = schemes
+    .filter_map(|scheme| match scheme {
+      SecurityScheme::Http { scheme, .. } => Some(scheme.to_case(Case::Pascal)),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  (!challenges.is_empty()).then(|| challenges.join(", "))
+}
+
+/// Statements, which authenticate request with one of operation security schemes into `user`
+/// variable, or answer with 401 if it is not possible.
+pub fn authenticate_user(op: &Operation, spec: &oas3::Spec) -> Option<TokenStream> {
+  let names = operation_schemes(op, spec);
+  if names.is_empty() {
+    return None;
+  }
+  let schemes = security_schemes(spec);
+  let operation_schemes = names
+    .iter()
+    .map(|name| {
+      let (_, scheme) = schemes
+        .iter()
+        .find(|(scheme_name, _)| scheme_name == name)
+        .expect("Security schemes of operations are checked");
+      (name, scheme)
+    })
+    .collect::<Vec<_>>();
+  let attempts = operation_schemes
+    .iter()
+    .map(|(name, scheme)| read_credentials(name, scheme));
+  let challenge = challenges(operation_schemes.iter().map(|(_, scheme)| *scheme))
+    .map(|challenges| quote!(.header(hyper::header::WWW_AUTHENTICATE, #challenges)));
+  Some(quote! {
+    let credentials = None #(#attempts)*;
+    let user = match credentials {
+      Some(credentials) => api.authenticate(credentials).await?,
+      None => None,
+    };
+    let user = match user {
+      Some(user) => user,
+      None => {
+        return Ok(
+          hyper::Response::builder()
+            .status(hyper::StatusCode::UNAUTHORIZED)
+            #challenge
+            .body(hyper::Body::from("Unauthorized"))?,
+        )
+      }
+    };
+  })
+}
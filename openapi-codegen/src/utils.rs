@@ -0,0 +1,154 @@
+use convert_case::{Case, Casing};
+use oas3::spec::{Operation, Parameter, SchemaType};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::options::Options;
+
+/// Rust keywords, which are valid identifiers only in raw form
+const KEYWORDS: &[&str] = &[
+  "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+  "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+  "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+  "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+  "where", "while", "yield",
+];
+
+/// Snake case identifier for property or parameter name. Keywords become raw identifiers,
+/// like `r#type`; the ones, which can't be raw, get trailing underscore.
+pub fn snake_ident(name: &str) -> Ident {
+  let snake = name.to_case(Case::Snake);
+  match snake.as_str() {
+    "self" | "super" | "crate" => Ident::new(&format!("{snake}_"), Span::call_site()),
+    keyword if KEYWORDS.contains(&keyword) => {
+      syn::parse_str(&format!("r#{keyword}")).expect("Keyword is valid raw identifier")
+    }
+    _ => Ident::new(&snake, Span::call_site()),
+  }
+}
+
+/// Pascal case identifier for type or variant name. Names, which are not identifiers, like the
+/// ones starting with digit, get leading underscore; `Self` gets trailing one.
+pub fn pascal_ident(name: &str) -> Ident {
+  let pascal = name.to_case(Case::Pascal);
+  match pascal.as_str() {
+    "Self" => Ident::new("Self_", Span::call_site()),
+    _ if is_ident(&pascal) => Ident::new(&pascal, Span::call_site()),
+    _ => Ident::new(&format!("_{pascal}"), Span::call_site()),
+  }
+}
+
+/// Name can be used as Rust type or variant name as it is
+pub fn is_ident(name: &str) -> bool {
+  syn::parse_str::<Ident>(name).is_ok()
+}
+
+/// Variants of string enum, one for each value. Values, which don't become distinct
+/// identifiers in Pascal case, are error.
+pub fn enum_variants(values: &[String]) -> Result<Vec<Ident>, String> {
+  let mut variants = Vec::new();
+  for value in values {
+    let variant = value.to_case(Case::Pascal);
+    match syn::parse_str::<Ident>(&variant) {
+      Ok(variant) if !variants.contains(&variant) => variants.push(variant),
+      Ok(_) => return Err(format!("Enum value {value:?} repeats variant {variant}")),
+      Err(_) => return Err(format!("Enum value {value:?} can't become Rust identifier")),
+    }
+  }
+  Ok(variants)
+}
+
+/// Rust type of primitive schema, `None` for objects and arrays. Formatted strings become
+/// `chrono` dates and times, `uuid` ids and bytes, unless options give type for the format.
+pub fn primitive_type(schema: &oas3::Schema, options: &Options) -> Option<TokenStream> {
+  let format = schema.format.as_deref();
+  let format_type = format.and_then(|format| options.format_type(format));
+  match schema.schema_type.as_ref()? {
+    SchemaType::Array | SchemaType::Object => None,
+    _ if format_type.is_some() => format_type,
+    SchemaType::Boolean => Some(quote!(bool)),
+    SchemaType::Integer | SchemaType::Number if format == Some("int32") => Some(quote!(i32)),
+    SchemaType::Integer | SchemaType::Number if format == Some("int64") => Some(quote!(i64)),
+    SchemaType::Integer => Some(quote!(i64)),
+    SchemaType::Number if format == Some("float") => Some(quote!(f32)),
+    SchemaType::Number => Some(quote!(f64)),
+    SchemaType::String => Some(match format {
+      Some("date-time") => quote!(chrono::DateTime<chrono::Utc>),
+      Some("date") => quote!(chrono::NaiveDate),
+      Some("time") => quote!(chrono::NaiveTime),
+      Some("uuid") => quote!(uuid::Uuid),
+      Some("byte") => quote!(Base64Bytes),
+      Some("binary") => quote!(Vec<u8>),
+      _ => quote!(String),
+    }),
+  }
+}
+
+/// Value of schema is a single string on the wire: primitive or string enum, which converts
+/// with `FromStr` and `Display`. Objects and compositions are serialized as a whole.
+pub fn is_scalar(schema: &oas3::Schema) -> bool {
+  let composed =
+    !(schema.all_of.is_empty() && schema.one_of.is_empty() && schema.any_of.is_empty());
+  match schema.schema_type {
+    Some(SchemaType::Array | SchemaType::Object) | None => false,
+    Some(_) => !composed,
+  }
+}
+
+/// Parameter is required, path parameters are always required
+pub fn is_required(param: &Parameter) -> bool {
+  param.location == "path" || param.required.unwrap_or(false)
+}
+
+/// Schema of parameter value
+pub fn param_schema(param: &Parameter, spec: &oas3::Spec) -> oas3::Schema {
+  param
+    .schema
+    .as_ref()
+    .and_then(|sc| sc.resolve(spec).ok())
+    .expect("Schemas of parameters are checked")
+}
+
+/// Rust type of parameter value: type generated for titled schema or primitive one
+pub fn param_value_type(param: &Parameter, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let schema = param_schema(param, spec);
+  match (&schema.title, primitive_type(&schema, options)) {
+    (Some(title), _) => options.titled_type(title),
+    (None, Some(primitive)) => primitive,
+    (None, None) => unreachable!("Titles of parameter schemas are checked"),
+  }
+}
+
+/// Type of parameter as operation receives it, not required ones are optional
+pub fn param_type(param: &Parameter, spec: &oas3::Spec, options: &Options) -> TokenStream {
+  let value_type = param_value_type(param, spec, options);
+  if is_required(param) {
+    value_type
+  } else {
+    quote!(Option<#value_type>)
+  }
+}
+
+/// Title of request body schema with all media types of the body. Every media type must have the
+/// same schema, so operation receives single body value.
+pub fn request_body_type(op: &Operation, spec: &oas3::Spec) -> Option<(String, Vec<String>)> {
+  let request_body = op.request_body.as_ref()?.resolve(spec).ok()?;
+  let mut body_type: Option<String> = None;
+  let mut mimes = Vec::new();
+  for (mime, media) in request_body.content.iter() {
+    let title = media
+      .schema
+      .as_ref()
+      .and_then(|sc| sc.resolve(spec).ok())
+      .and_then(|sc| sc.title)
+      .expect("Titles of request body schemas are checked");
+    match &body_type {
+      Some(body_type) if *body_type != title => {
+        unreachable!("Request body schemas are checked to be the same")
+      }
+      _ => body_type = Some(title),
+    }
+    mimes.push(mime.to_owned());
+  }
+  body_type.map(|body_type| (body_type, mimes))
+}
@@ -0,0 +1,45 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  process::Command,
+};
+
+const SPEC: &str = "../habfoo-api/api/root.yaml";
+
+/// Empty directory for generated files of the test
+fn output_dir(test: &str) -> PathBuf {
+  let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test);
+  let _ = fs::remove_dir_all(&dir);
+  dir
+}
+
+fn codegen(output_dir: &Path, check: bool) -> bool {
+  let mut command = Command::new(env!("CARGO_BIN_EXE_openapi-codegen"));
+  command.arg(SPEC).arg(output_dir);
+  if check {
+    command.arg("--check");
+  }
+  command.output().unwrap().status.success()
+}
+
+#[test]
+fn check_passes_on_fresh_output() {
+  let dir = output_dir("fresh");
+  assert!(codegen(&dir, false));
+  assert!(codegen(&dir, true));
+
+  let types = fs::read_to_string(dir.join("types.rs")).unwrap();
+  assert!(types.starts_with("// Generated by openapi-codegen from root.yaml, do not edit."));
+}
+
+#[test]
+fn check_fails_on_stale_output() {
+  let dir = output_dir("stale");
+  assert!(!codegen(&dir, true));
+
+  assert!(codegen(&dir, false));
+  let mut traits = fs::read_to_string(dir.join("traits.rs")).unwrap();
+  traits.push_str("// Edited by hand\n");
+  fs::write(dir.join("traits.rs"), traits).unwrap();
+  assert!(!codegen(&dir, true));
+}
@@ -1,72 +1,148 @@
-use std::path::Path;
+//! `types!` macro generates modules of the api from OpenAPI spec. Path of the spec is relative to
+//! the crate, which calls the macro, options follow it as `key = value` pairs:
+//!
+//! ```ignore
+//! types!(
+//!   "../habfoo-api/api/root.yaml",
+//!   modules = [types, traits, client],
+//!   type_prefix = "Api",
+//!   derives = [Eq, Hash],
+//!   formats = { "barcode" => crate::Barcode },
+//!   titles = { "NutrientKind" => crate::NutrientKind },
+//!   trait_name = HabfooApi,
+//! );
+//! ```
 
-use litrs::StringLit;
-use oas3::OpenApiV3Spec;
+use std::{
+  collections::BTreeMap,
+  path::{Path, PathBuf},
+};
+
+use openapi_codegen::{read_spec, Modules, Options};
 use proc_macro::TokenStream;
-use quote::quote;
-mod generate_routing;
-mod generate_trait;
-mod generate_types;
-pub(crate) mod utils;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned};
+use syn::{
+  braced, bracketed,
+  parse::{Parse, ParseStream},
+  parse_macro_input,
+  punctuated::Punctuated,
+  Ident, LitStr, Token,
+};
+
+/// Arguments of the macro: spec path with generator options
+struct Args {
+  path: LitStr,
+  options: Options,
+}
 
-fn extract_path(input: TokenStream) -> Result<String, String> {
-  let mut items = input.into_iter().collect::<Vec<_>>();
-  if items.len() != 1 {
-    Err("Expected only one argument".into())
-  } else {
-    let token = items.remove(0);
-    match StringLit::try_from(token) {
-      Ok(token) => Ok(token.value().to_owned()),
-      Err(err) => Err(err.to_compile_error().to_string()),
+/// `[a, b, c]` list
+fn list<T: Parse>(input: ParseStream) -> syn::Result<Vec<T>> {
+  let content;
+  bracketed!(content in input);
+  let items = Punctuated::<T, Token![,]>::parse_terminated(&content)?;
+  Ok(items.into_iter().collect())
+}
+
+/// `{ "key" => Type, .. }` map of types
+fn type_map(input: ParseStream) -> syn::Result<BTreeMap<String, syn::Type>> {
+  let content;
+  braced!(content in input);
+  let mut types = BTreeMap::new();
+  while !content.is_empty() {
+    let key = content.parse::<LitStr>()?.value();
+    content.parse::<Token![=>]>()?;
+    types.insert(key, content.parse()?);
+    if !content.is_empty() {
+      content.parse::<Token![,]>()?;
+    }
+  }
+  Ok(types)
+}
+
+impl Parse for Args {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let path = input.parse::<LitStr>()?;
+    let mut options = Options::default();
+    while !input.is_empty() {
+      input.parse::<Token![,]>()?;
+      if input.is_empty() {
+        break;
+      }
+      let key = input.parse::<Ident>()?;
+      input.parse::<Token![=]>()?;
+      match key.to_string().as_str() {
+        "modules" => {
+          let modules = list::<Ident>(input)?;
+          options.modules = modules.iter().map(ToString::to_string).collect();
+          options
+            .check_modules()
+            .map_err(|err| syn::Error::new(key.span(), err))?;
+        }
+        "type_prefix" => options.type_prefix = input.parse::<LitStr>()?.value(),
+        "derives" => options.derives = list(input)?,
+        "formats" => options.formats = type_map(input)?,
+        "titles" => options.titles = type_map(input)?,
+        "trait_name" => options.trait_name = Some(input.parse::<Ident>()?.to_string()),
+        _ => {
+          let message = format!(
+            "Unknown option {key}, expected modules, type_prefix, derives, formats, titles or \
+             trait_name"
+          );
+          return Err(syn::Error::new(key.span(), message));
+        }
+      }
     }
+    Ok(Args { path, options })
   }
 }
 
-fn read_spec<P>(path: P) -> Result<OpenApiV3Spec, String>
-where
-  P: AsRef<Path>,
-{
-  let root = path.as_ref().parent();
-  let file = path.as_ref().file_name();
-  if let (Some(root), Some(file)) = (root, file) {
-    oas3::from_path_dir(root.display().to_string(), file.to_str().unwrap()).map_err(|err| {
-      format!(
-        "\"Cannot parse file: {} Error: {err}\"",
-        path.as_ref().display()
+/// Spec path relative to the crate, which calls the macro
+fn spec_path(path: &str) -> PathBuf {
+  let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+  let path = Path::new(&manifest_dir).join(path);
+  path.canonicalize().unwrap_or(path)
+}
+
+/// Includes files, so cargo rebuilds the crate, when any of them changes
+fn track_files(files: &[PathBuf]) -> proc_macro2::TokenStream {
+  files
+    .iter()
+    .map(|file| {
+      let file = file.display().to_string();
+      quote!(
+        const _: &[u8] = include_bytes!(#file);
       )
     })
-  } else {
-    Err("Cannot get filename and path correctly".into())
-  }
+    .collect()
 }
 
 #[proc_macro]
 pub fn types(input: TokenStream) -> TokenStream {
-  let schema = extract_path(input).and_then(read_spec);
+  let Args { path, options } = parse_macro_input!(input as Args);
+  let span: Span = path.span();
 
-  let q = match schema {
-    Ok(openapi) => {
-      let router = generate_routing::run(&openapi);
-      let api_trait = generate_trait::run(&openapi);
-      let types = generate_types::run(&openapi);
-      quote! {
-        pub mod types {
-          #types
-        }
-        pub mod traits {
-          #api_trait
-        }
-        pub mod router {
-          #router
-        }
-      }
-    }
-    Err(err) => {
-      quote! {
-        compile_error!(#err);
-      }
-    }
+  let path = spec_path(&path.value());
+  let openapi = match read_spec(&path) {
+    Ok(openapi) => openapi,
+    Err(err) => return TokenStream::from(quote_spanned!(span=> compile_error!(#err);)),
+  };
+
+  let q = match Modules::generate(&openapi, &path.display().to_string(), &options) {
+    Ok(modules) => modules.into_tokens(),
+    // Each problem is separate error, so all of them are shown at once
+    Err(problems) => problems
+      .iter()
+      .map(|problem| {
+        let message = problem.to_string();
+        quote_spanned!(span=> compile_error!(#message);)
+      })
+      .collect(),
   };
+  // Files are read while modules are generated, so all of them are known by now
+  let mut files = vec![path];
+  files.extend(openapi.read_files());
+  let tracking = track_files(&files);
 
-  TokenStream::from(q)
+  TokenStream::from(quote!(#tracking #q))
 }
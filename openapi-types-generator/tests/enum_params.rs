@@ -0,0 +1,90 @@
+use openapi_types_generator::types;
+use routerify::RouterService;
+
+types!("tests/enum-params.yaml");
+
+struct Week;
+
+#[async_trait::async_trait]
+impl traits::Week for Week {
+  async fn list_days(&self) -> Result<Vec<types::Day>, traits::Error> {
+    Ok(vec![types::Day::Monday, types::Day::DayOff])
+  }
+
+  async fn get_day(
+    &self,
+    day: types::Day,
+    next: Option<types::Day>,
+  ) -> Result<types::Days, traits::Error> {
+    Ok(types::Days { day, next })
+  }
+
+  async fn get_days_header(&self, days: types::Days) -> Result<types::Days, traits::Error> {
+    Ok(days)
+  }
+
+  async fn get_note(&self, note: String) -> Result<String, traits::Error> {
+    Ok(note)
+  }
+}
+
+/// Base url of the api served on a free local port
+fn serve() -> String {
+  let service = RouterService::new(router::create_routing_table(Week)).unwrap();
+  let server = hyper::server::Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+  let url = format!("http://{}", server.local_addr());
+  tokio::spawn(server);
+  url
+}
+
+#[tokio::test]
+async fn enum_params_round_trip() {
+  let client = client::Client::new(serve());
+  let days = client
+    .get_day(types::Day::DayOff, Some(types::Day::Monday))
+    .await
+    .unwrap();
+  assert_eq!(days.day, types::Day::DayOff);
+  assert_eq!(days.next, Some(types::Day::Monday));
+
+  let days = client.get_day(types::Day::Monday, None).await.unwrap();
+  assert_eq!(days.next, None);
+}
+
+#[tokio::test]
+async fn inline_array_response_round_trip() {
+  let client = client::Client::new(serve());
+  let days = client.list_days().await.unwrap();
+  assert_eq!(days, vec![types::Day::Monday, types::Day::DayOff]);
+}
+
+#[tokio::test]
+async fn object_header_round_trip() {
+  let client = client::Client::new(serve());
+  let days = types::Days {
+    day: types::Day::DayOff,
+    next: Some(types::Day::Monday),
+  };
+  assert_eq!(client.get_days_header(days.clone()).await.unwrap(), days);
+}
+
+#[tokio::test]
+async fn reports_query_parse_error() {
+  let uri = format!("{}/days/monday?next[0]=monday", serve());
+  let response = hyper::Client::new()
+    .get(uri.parse().unwrap())
+    .await
+    .unwrap();
+  assert_eq!(response.status(), hyper::StatusCode::BAD_REQUEST);
+  let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+  let problem = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+  assert_eq!(problem["invalidParams"][0]["in"], "query");
+  assert_eq!(problem["invalidParams"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn cookie_round_trip() {
+  let client = client::Client::new(serve());
+  let note = "Tea; milk=50% & sugar".to_owned();
+  assert_eq!(client.get_note(note.clone()).await.unwrap(), note);
+}
@@ -0,0 +1,22 @@
+use openapi_types_generator::types;
+
+types!("tests/one-of.yaml", modules = [types], type_prefix = "Api");
+
+#[test]
+fn discriminated_one_of_round_trip() {
+  let cat = serde_json::json!({"petType": "cat", "name": "Tom"});
+  let pet = serde_json::from_value::<types::ApiPet>(cat.clone()).unwrap();
+  assert_eq!(
+    pet,
+    types::ApiPet::Cat(types::ApiCat { name: "Tom".into() })
+  );
+  assert_eq!(serde_json::to_value(&pet).unwrap(), cat);
+
+  let bird = serde_json::json!({"petType": "1Bird", "wings": 2});
+  let pet = serde_json::from_value::<types::ApiPet>(bird.clone()).unwrap();
+  assert_eq!(
+    pet,
+    types::ApiPet::_1Bird(types::Api1Bird { wings: Some(2) })
+  );
+  assert_eq!(serde_json::to_value(&pet).unwrap(), bird);
+}